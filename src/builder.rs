@@ -0,0 +1,361 @@
+//! A builder combining the options of the individual load and store functions
+
+use crate::{
+    config_metadata_path, configuration_file_path_scoped, fs_rename, load_path,
+    serialize_with_options, write_config_rotating, ConfigMeta, ConfigScope, ConfyError,
+    StoreOptions, DEFAULT_CONFIG_NAME, EXTENSION,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs::Permissions;
+use std::path::{Path, PathBuf};
+
+/// A builder for loading and storing a configuration with custom options
+///
+/// This combines the options of the various `load_*` and `store_*`
+/// functions in one place.  Once configured, [`build`] resolves the
+/// configuration file path into a [`ConfyHandle`].
+///
+/// ```rust,no_run
+/// # use confy::{ConfyBuilder, ConfyError};
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {
+///     version: u8,
+/// }
+///
+/// let handle = ConfyBuilder::new("my-app-name")
+///     .config_name("settings")
+///     .backup(3)
+///     .build()?;
+/// let mut cfg: MyConfig = handle.load()?;
+/// cfg.version += 1;
+/// handle.store(&cfg)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`build`]: #method.build
+/// [`ConfyHandle`]: struct.ConfyHandle.html
+#[derive(Debug, Clone)]
+pub struct ConfyBuilder {
+    app_name: String,
+    config_name: Option<String>,
+    scope: ConfigScope,
+    extension: Option<String>,
+    options: StoreOptions,
+    backup: usize,
+    mode: Option<u32>,
+}
+
+impl ConfyBuilder {
+    /// Start building the configuration of the application `app_name`.
+    pub fn new(app_name: impl Into<String>) -> Self {
+        ConfyBuilder {
+            app_name: app_name.into(),
+            config_name: None,
+            scope: ConfigScope::Roaming,
+            extension: None,
+            options: StoreOptions::default(),
+            backup: 0,
+            mode: None,
+        }
+    }
+
+    /// Set the application name.
+    pub fn app_name(mut self, app_name: impl Into<String>) -> Self {
+        self.app_name = app_name.into();
+        self
+    }
+
+    /// Set the configuration name, `"default-config"` by default.
+    pub fn config_name(mut self, config_name: impl Into<String>) -> Self {
+        self.config_name = Some(config_name.into());
+        self
+    }
+
+    /// Set the [`ConfigScope`], `Roaming` by default.
+    ///
+    /// [`ConfigScope`]: enum.ConfigScope.html
+    pub fn scope(mut self, scope: ConfigScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Use a custom file extension, given without the leading dot.
+    pub fn extension(mut self, extension: impl Into<String>) -> Self {
+        self.extension = Some(extension.into());
+        self
+    }
+
+    /// Set [`StoreOptions::atomic`], on by default.
+    ///
+    /// [`StoreOptions::atomic`]: struct.StoreOptions.html#structfield.atomic
+    pub fn atomic(mut self, atomic: bool) -> Self {
+        self.options.atomic = atomic;
+        self
+    }
+
+    /// Keep up to `keep` numbered previous versions when storing, like
+    /// [`store_path_rotating`].  None are kept by default.
+    ///
+    /// [`store_path_rotating`]: fn.store_path_rotating.html
+    pub fn backup(mut self, keep: usize) -> Self {
+        self.backup = keep;
+        self
+    }
+
+    /// Set the Unix permission bits of the stored file, like
+    /// [`store_path_perms`].  This is ignored on other platforms.
+    ///
+    /// [`store_path_perms`]: fn.store_path_perms.html
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Set all other [`StoreOptions`] at once.  Options set by other methods
+    /// of the builder are overwritten.
+    ///
+    /// [`StoreOptions`]: struct.StoreOptions.html
+    pub fn store_options(mut self, options: StoreOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Resolve the configuration file path.
+    pub fn build(self) -> Result<ConfyHandle, ConfyError> {
+        let path = configuration_file_path_scoped(
+            &self.app_name,
+            self.config_name.as_deref(),
+            DEFAULT_CONFIG_NAME,
+            self.extension.as_deref().unwrap_or(EXTENSION),
+            self.scope,
+        )?;
+        Ok(ConfyHandle {
+            path,
+            options: self.options,
+            backup: self.backup,
+            mode: self.mode,
+        })
+    }
+}
+
+/// A configuration file with the options of a [`ConfyBuilder`]
+///
+/// [`ConfyBuilder`]: struct.ConfyBuilder.html
+#[derive(Debug, Clone)]
+pub struct ConfyHandle {
+    path: PathBuf,
+    options: StoreOptions,
+    backup: usize,
+    mode: Option<u32>,
+}
+
+impl ConfyHandle {
+    /// The path of the configuration file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the configuration, see [`load_path`].
+    ///
+    /// [`load_path`]: fn.load_path.html
+    pub fn load<T: Serialize + DeserializeOwned + Default>(&self) -> Result<T, ConfyError> {
+        load_path(&self.path)
+    }
+
+    /// Load the configuration and keep it, to tell whether it changed when
+    /// reloading, see [`Config`].
+    ///
+    /// [`Config`]: struct.Config.html
+    pub fn load_cached<T: Serialize + DeserializeOwned + Default>(
+        self,
+    ) -> Result<Config<T>, ConfyError> {
+        let meta = config_metadata_path(&self.path)?;
+        let value = self.load()?;
+        Ok(Config {
+            handle: self,
+            value,
+            meta,
+        })
+    }
+
+    /// Save changes made to the configuration, see [`store_path`].
+    ///
+    /// [`store_path`]: fn.store_path.html
+    pub fn store<T: Serialize>(&self, cfg: &T) -> Result<(), ConfyError> {
+        let s = serialize_with_options(cfg, &self.options)?;
+        write_config_rotating(
+            &self.path,
+            s,
+            self.permissions(),
+            &self.options,
+            fs_rename,
+            self.backup,
+        )
+    }
+
+    #[cfg(unix)]
+    fn permissions(&self) -> Option<Permissions> {
+        use std::os::unix::fs::PermissionsExt;
+        self.mode.map(Permissions::from_mode)
+    }
+
+    #[cfg(not(unix))]
+    fn permissions(&self) -> Option<Permissions> {
+        None
+    }
+}
+
+/// A configuration loaded through a [`ConfyHandle`], kept for reloading
+///
+/// ```rust,no_run
+/// # use confy::{ConfyBuilder, ConfyError};
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, PartialEq, Serialize, Deserialize)]
+/// struct MyConfig {
+///     version: u8,
+/// }
+///
+/// let mut config = ConfyBuilder::new("my-app-name")
+///     .build()?
+///     .load_cached::<MyConfig>()?;
+/// // e.g. on "Reload config" in a menu
+/// if !config.reload()? {
+///     println!("No changes detected");
+/// }
+/// println!("version {}", config.get().version);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`ConfyHandle`]: struct.ConfyHandle.html
+#[derive(Debug, Clone)]
+pub struct Config<T> {
+    handle: ConfyHandle,
+    value: T,
+    meta: Option<ConfigMeta>,
+}
+
+impl<T: Serialize + DeserializeOwned + Default + PartialEq> Config<T> {
+    /// The configuration as last loaded.
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// The handle the configuration is loaded through.
+    pub fn handle(&self) -> &ConfyHandle {
+        &self.handle
+    }
+
+    /// Load the configuration again, and return whether it changed.
+    ///
+    /// If the size and modification time of the file are unchanged, it is
+    /// not read at all.
+    pub fn reload(&mut self) -> Result<bool, ConfyError> {
+        let meta = config_metadata_path(self.handle.path())?;
+        if meta.is_some() && meta == self.meta {
+            return Ok(false);
+        }
+        let value = self.handle.load()?;
+        self.meta = meta;
+        if value == self.value {
+            return Ok(false);
+        }
+        self.value = value;
+        Ok(true)
+    }
+
+    /// Take the configuration as last loaded.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_configuration_file_path_with_extension;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(PartialEq, Default, Debug, Serialize, Deserialize)]
+    struct ExampleConfig {
+        name: String,
+        count: usize,
+    }
+
+    #[test]
+    fn build_resolves_path() {
+        let handle = ConfyBuilder::new("example-app")
+            .config_name("example-config")
+            .extension("cfg")
+            .build()
+            .expect("build failed");
+        assert_eq!(
+            handle.path(),
+            get_configuration_file_path_with_extension("example-app", "example-config", "cfg")
+                .unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn store_with_backup_and_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("example-config.{}", EXTENSION));
+        let handle = ConfyHandle {
+            path: path.clone(),
+            ..ConfyBuilder::new("example-app")
+                .backup(1)
+                .mode(0o600)
+                .build()
+                .unwrap()
+        };
+        let config = ExampleConfig {
+            name: "Test".to_string(),
+            count: 42,
+        };
+        handle
+            .store(&ExampleConfig::default())
+            .expect("store failed");
+        handle.store(&config).expect("store failed");
+        assert_eq!(handle.load::<ExampleConfig>().unwrap(), config);
+
+        let backup = path.with_file_name(format!("example-config.1.{}", EXTENSION));
+        assert_eq!(
+            load_path::<ExampleConfig>(backup).unwrap(),
+            ExampleConfig::default()
+        );
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn reload_reports_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = ConfyHandle {
+            path: dir.path().join(format!("example-config.{}", EXTENSION)),
+            ..ConfyBuilder::new("example-app").build().unwrap()
+        };
+        handle.store(&ExampleConfig::default()).unwrap();
+        let mut config = handle.clone().load_cached::<ExampleConfig>().unwrap();
+        assert!(!config.reload().expect("reload failed"));
+
+        // Rewritten with the same content.
+        handle.store(&ExampleConfig::default()).unwrap();
+        assert!(!config.reload().expect("reload failed"));
+
+        let changed = ExampleConfig {
+            name: "Test".to_string(),
+            count: 42,
+        };
+        handle.store(&changed).unwrap();
+        assert!(config.reload().expect("reload failed"));
+        assert_eq!(config.get(), &changed);
+        assert_eq!(config.into_inner(), changed);
+    }
+}
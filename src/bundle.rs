@@ -0,0 +1,262 @@
+//! Export and import of whole configuration directories as tar archives
+//!
+//! Only the subset of the ustar format needed for configuration
+//! directories is supported: regular files and directories.
+
+use crate::{check_store_guard, ConfyError};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+const BLOCK: usize = 512;
+
+enum Entry {
+    Directory(String),
+    File(String, Vec<u8>),
+}
+
+/// Write all files below `dir` into a tar archive at `dst`.
+pub fn export(dir: &Path, dst: &Path) -> Result<(), ConfyError> {
+    let mut entries = Vec::new();
+    collect(dir, "", &mut entries).map_err(ConfyError::ReadConfigurationFileError)?;
+
+    let mut archive = Vec::new();
+    for entry in &entries {
+        match entry {
+            Entry::Directory(name) => {
+                archive.extend_from_slice(&header(&format!("{}/", name), 0, b'5', 0o755)?)
+            }
+            Entry::File(name, data) => {
+                archive.extend_from_slice(&header(name, data.len() as u64, b'0', 0o644)?);
+                archive.extend_from_slice(data);
+                archive.resize(padded(archive.len()), 0);
+            }
+        }
+    }
+    archive.resize(archive.len() + 2 * BLOCK, 0);
+
+    let mut f = File::create(dst).map_err(ConfyError::OpenConfigurationFileError)?;
+    f.write_all(&archive)
+        .map_err(ConfyError::WriteConfigurationFileError)?;
+    f.flush().map_err(ConfyError::WriteConfigurationFileError)
+}
+
+/// Replace `dir` with the content of the tar archive at `src`.
+pub fn import(dir: &Path, src: &Path) -> Result<(), ConfyError> {
+    let mut archive = Vec::new();
+    File::open(src)
+        .and_then(|mut f| f.read_to_end(&mut archive))
+        .map_err(ConfyError::ReadConfigurationFileError)?;
+    // Parse and validate everything before touching the existing directory.
+    let entries = parse(&archive)?;
+    for entry in &entries {
+        if let Entry::File(name, data) = entry {
+            check_store_guard(&dir.join(name), &String::from_utf8_lossy(data))?;
+        }
+    }
+
+    let staging = sibling(dir, "import");
+    let previous = sibling(dir, "previous");
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(ConfyError::DirectoryCreationFailed)?;
+    for entry in &entries {
+        match entry {
+            Entry::Directory(name) => fs::create_dir_all(staging.join(name))
+                .map_err(ConfyError::DirectoryCreationFailed)?,
+            Entry::File(name, data) => {
+                let path = staging.join(name);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(ConfyError::DirectoryCreationFailed)?;
+                }
+                fs::write(path, data).map_err(ConfyError::WriteConfigurationFileError)?;
+            }
+        }
+    }
+
+    let _ = fs::remove_dir_all(&previous);
+    if dir.exists() {
+        fs::rename(dir, &previous).map_err(ConfyError::WriteConfigurationFileError)?;
+    } else if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent).map_err(ConfyError::DirectoryCreationFailed)?;
+    }
+    if let Err(e) = fs::rename(&staging, dir) {
+        let _ = fs::rename(&previous, dir);
+        return Err(ConfyError::WriteConfigurationFileError(e));
+    }
+    let _ = fs::remove_dir_all(&previous);
+    Ok(())
+}
+
+fn sibling(dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = dir.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}-{}", suffix, std::process::id()));
+    dir.with_file_name(name)
+}
+
+fn collect(dir: &Path, prefix: &str, entries: &mut Vec<Entry>) -> std::io::Result<()> {
+    let mut children = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    children.sort_by_key(|child| child.file_name());
+    for child in children {
+        let name = match child.file_name().into_string() {
+            Ok(name) => format!("{}{}", prefix, name),
+            Err(name) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{:?} is not valid Unicode", name),
+                ))
+            }
+        };
+        let file_type = child.file_type()?;
+        if file_type.is_dir() {
+            entries.push(Entry::Directory(name.clone()));
+            collect(&child.path(), &format!("{}/", name), entries)?;
+        } else if file_type.is_file() {
+            entries.push(Entry::File(name, fs::read(child.path())?));
+        }
+    }
+    Ok(())
+}
+
+fn padded(len: usize) -> usize {
+    len.div_ceil(BLOCK) * BLOCK
+}
+
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+}
+
+fn header(name: &str, size: u64, kind: u8, mode: u64) -> Result<[u8; BLOCK], ConfyError> {
+    let mut block = [0u8; BLOCK];
+
+    let (prefix, name) = if name.len() <= 100 {
+        ("", name)
+    } else {
+        name.char_indices()
+            .filter(|(i, c)| *c == '/' && *i <= 155 && name.len() - i - 1 <= 100)
+            .map(|(i, _)| (&name[..i], &name[i + 1..]))
+            .next()
+            .ok_or_else(|| ConfyError::BadBundle(format!("path {:?} is too long", name)))?
+    };
+    block[..name.len()].copy_from_slice(name.as_bytes());
+    block[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    octal(&mut block[100..108], mode);
+    octal(&mut block[108..116], 0);
+    octal(&mut block[116..124], 0);
+    octal(&mut block[124..136], size);
+    octal(&mut block[136..148], 0);
+    block[156] = kind;
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+
+    block[148..156].copy_from_slice(b"        ");
+    let checksum: u64 = block.iter().map(|b| u64::from(*b)).sum();
+    octal(&mut block[148..155], checksum);
+    Ok(block)
+}
+
+fn field(bytes: &[u8]) -> Result<&str, ConfyError> {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..end])
+        .map_err(|_| ConfyError::BadBundle("entry name is not valid UTF-8".to_string()))
+}
+
+fn parse_octal(bytes: &[u8]) -> Result<u64, ConfyError> {
+    let digits = field(bytes)?.trim_matches(|c| c == ' ' || c == '\0');
+    u64::from_str_radix(digits, 8)
+        .map_err(|_| ConfyError::BadBundle(format!("invalid number {:?}", digits)))
+}
+
+fn parse(archive: &[u8]) -> Result<Vec<Entry>, ConfyError> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    loop {
+        let block = archive
+            .get(offset..offset + BLOCK)
+            .ok_or_else(|| ConfyError::BadBundle("archive is truncated".to_string()))?;
+        if block.iter().all(|b| *b == 0) {
+            return Ok(entries);
+        }
+        if &block[257..262] != b"ustar" {
+            return Err(ConfyError::BadBundle("not a tar archive".to_string()));
+        }
+        let checksum: u64 = block
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if (148..156).contains(&i) {
+                    32
+                } else {
+                    u64::from(*b)
+                }
+            })
+            .sum();
+        if parse_octal(&block[148..156])? != checksum {
+            return Err(ConfyError::BadBundle(
+                "header checksum mismatch".to_string(),
+            ));
+        }
+
+        let prefix = field(&block[345..500])?;
+        let name = field(&block[..100])?;
+        let name = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let mut parts = Vec::new();
+        for component in Path::new(&name).components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy()),
+                Component::CurDir => {}
+                _ => {
+                    return Err(ConfyError::BadBundle(format!(
+                        "invalid entry path {:?}",
+                        name
+                    )))
+                }
+            }
+        }
+        let name = parts.join("/");
+
+        let size = parse_octal(&block[124..136])? as usize;
+        offset += BLOCK;
+        match block[156] {
+            // The archive root itself, e.g. `./`.
+            b'5' if name.is_empty() => {}
+            b'5' => entries.push(Entry::Directory(name)),
+            b'0' | 0 if !name.is_empty() => {
+                let data = archive
+                    .get(offset..offset + size)
+                    .ok_or_else(|| ConfyError::BadBundle("archive is truncated".to_string()))?;
+                entries.push(Entry::File(name, data.to_vec()));
+            }
+            kind => {
+                return Err(ConfyError::BadBundle(format!(
+                    "unsupported entry type {:?} for {:?}",
+                    kind as char, name
+                )))
+            }
+        }
+        offset += padded(size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_escaping_paths() {
+        let mut archive = header("../evil", 0, b'0', 0o644).unwrap().to_vec();
+        archive.resize(archive.len() + 2 * BLOCK, 0);
+        assert!(matches!(parse(&archive), Err(ConfyError::BadBundle(_))));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(matches!(parse(&[1; BLOCK]), Err(ConfyError::BadBundle(_))));
+        assert!(matches!(parse(&[]), Err(ConfyError::BadBundle(_))));
+    }
+}
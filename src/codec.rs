@@ -0,0 +1,277 @@
+//! Pluggable serialization of configuration objects
+
+use crate::ConfyError;
+#[cfg(feature = "toml_conf")]
+use serde::ser;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A configuration file format
+///
+/// Implement this to store configurations in a format not built into confy,
+/// while keeping confy's handling of paths and atomic writes, see
+/// [`load_path_with_codec`] and [`store_path_with_codec`].
+///
+/// [`load_path_with_codec`]: fn.load_path_with_codec.html
+/// [`store_path_with_codec`]: fn.store_path_with_codec.html
+pub trait Codec {
+    /// Serialize a configuration object into the content of a file.
+    fn to_string<T: Serialize>(&self, cfg: &T) -> Result<String, ConfyError>;
+
+    /// Deserialize a configuration object from the content of a file.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_str<T: DeserializeOwned>(&self, s: &str) -> Result<T, ConfyError>;
+}
+
+/// The TOML format used by default
+#[cfg(feature = "toml_conf")]
+#[derive(Debug, Clone, Copy)]
+pub struct TomlCodec {
+    /// Whether to serialize with [`toml::to_string_pretty`], the default.
+    ///
+    /// [`toml::to_string_pretty`]: https://docs.rs/toml/0.5/toml/ser/fn.to_string_pretty.html
+    pub pretty: bool,
+}
+
+#[cfg(feature = "toml_conf")]
+impl Default for TomlCodec {
+    fn default() -> Self {
+        TomlCodec { pretty: true }
+    }
+}
+
+#[cfg(feature = "toml_conf")]
+impl Codec for TomlCodec {
+    fn to_string<T: Serialize>(&self, cfg: &T) -> Result<String, ConfyError> {
+        // The serializer happily writes e.g. a bare array, which is no valid
+        // TOML document.
+        if !cfg
+            .serialize(TableProbe)
+            .map_err(ConfyError::SerializeTomlError)?
+        {
+            return Err(ConfyError::SerializeTomlError(
+                toml::ser::Error::UnsupportedType,
+            ));
+        }
+        if self.pretty {
+            toml::to_string_pretty(cfg).map_err(ConfyError::SerializeTomlError)
+        } else {
+            toml::to_string(cfg).map_err(ConfyError::SerializeTomlError)
+        }
+    }
+
+    fn from_str<T: DeserializeOwned>(&self, s: &str) -> Result<T, ConfyError> {
+        toml::from_str(s).map_err(ConfyError::BadTomlData)
+    }
+}
+
+#[cfg(feature = "toml_conf")]
+macro_rules! not_a_table {
+    ($($method:ident: $type:ty),*) => {$(
+        fn $method(self, _: $type) -> Result<bool, toml::ser::Error> {
+            Ok(false)
+        }
+    )*};
+}
+
+/// A serializer telling whether a value serializes as a table, without
+/// serializing any of its fields.
+#[cfg(feature = "toml_conf")]
+struct TableProbe;
+
+#[cfg(feature = "toml_conf")]
+impl ser::Serializer for TableProbe {
+    type Ok = bool;
+    type Error = toml::ser::Error;
+    type SerializeSeq = Skip;
+    type SerializeTuple = Skip;
+    type SerializeTupleStruct = Skip;
+    type SerializeTupleVariant = Skip;
+    type SerializeMap = Skip;
+    type SerializeStruct = Skip;
+    type SerializeStructVariant = Skip;
+
+    not_a_table!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+        serialize_bytes: &[u8],
+        serialize_unit_struct: &'static str
+    );
+    fn serialize_none(self) -> Result<bool, toml::ser::Error> {
+        Ok(false)
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<bool, toml::ser::Error> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<bool, toml::ser::Error> {
+        Ok(false)
+    }
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<bool, toml::ser::Error> {
+        Ok(false)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<bool, toml::ser::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<bool, toml::ser::Error> {
+        value.serialize(self)
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Skip, toml::ser::Error> {
+        Ok(Skip(false))
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Skip, toml::ser::Error> {
+        Ok(Skip(false))
+    }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Skip, toml::ser::Error> {
+        Ok(Skip(false))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Skip, toml::ser::Error> {
+        Ok(Skip(false))
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Skip, toml::ser::Error> {
+        Ok(Skip(true))
+    }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Skip, toml::ser::Error> {
+        Ok(Skip(true))
+    }
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Skip, toml::ser::Error> {
+        Ok(Skip(false))
+    }
+}
+
+/// Ignores the elements of a compound value, ending with whether it is a
+/// table.
+#[cfg(feature = "toml_conf")]
+struct Skip(bool);
+
+#[cfg(feature = "toml_conf")]
+macro_rules! skip_elements {
+    ($($trait:ident::$method:ident),*) => {$(
+        impl ser::$trait for Skip {
+            type Ok = bool;
+            type Error = toml::ser::Error;
+
+            fn $method<T: ?Sized + Serialize>(&mut self, _: &T) -> Result<(), toml::ser::Error> {
+                Ok(())
+            }
+
+            fn end(self) -> Result<bool, toml::ser::Error> {
+                Ok(self.0)
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "toml_conf")]
+skip_elements!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+#[cfg(feature = "toml_conf")]
+impl ser::SerializeMap for Skip {
+    type Ok = bool;
+    type Error = toml::ser::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, _: &T) -> Result<(), toml::ser::Error> {
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, _: &T) -> Result<(), toml::ser::Error> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<bool, toml::ser::Error> {
+        Ok(self.0)
+    }
+}
+
+#[cfg(feature = "toml_conf")]
+impl ser::SerializeStruct for Skip {
+    type Ok = bool;
+    type Error = toml::ser::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), toml::ser::Error> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<bool, toml::ser::Error> {
+        Ok(self.0)
+    }
+}
+
+#[cfg(feature = "toml_conf")]
+impl ser::SerializeStructVariant for Skip {
+    type Ok = bool;
+    type Error = toml::ser::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), toml::ser::Error> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<bool, toml::ser::Error> {
+        Ok(self.0)
+    }
+}
+
+/// The YAML format used with the `yaml_conf` feature
+#[cfg(feature = "yaml_conf")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct YamlCodec;
+
+#[cfg(feature = "yaml_conf")]
+impl Codec for YamlCodec {
+    fn to_string<T: Serialize>(&self, cfg: &T) -> Result<String, ConfyError> {
+        serde_yaml::to_string(cfg).map_err(ConfyError::SerializeYamlError)
+    }
+
+    fn from_str<T: DeserializeOwned>(&self, s: &str) -> Result<T, ConfyError> {
+        serde_yaml::from_str(s).map_err(ConfyError::BadYamlData)
+    }
+}
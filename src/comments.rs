@@ -0,0 +1,179 @@
+//! Injection of comments into serialized configurations
+
+/// Insert `# comment` lines above the keys listed in `comments`.
+///
+/// Keys are given as dotted paths from the document root, e.g.
+/// `server.port`.  Keys without a comment are left untouched.
+pub fn inject(s: &str, comments: &[(&str, &str)]) -> String {
+    if comments.is_empty() {
+        return s.to_string();
+    }
+
+    #[cfg(feature = "toml_conf")]
+    {
+        inject_toml(s, comments)
+    }
+    #[cfg(feature = "yaml_conf")]
+    {
+        inject_yaml(s, comments)
+    }
+}
+
+fn push_comment(out: &mut String, indent: &str, comment: &str) {
+    for line in comment.lines() {
+        out.push_str(indent);
+        if line.is_empty() {
+            out.push('#');
+        } else {
+            out.push_str("# ");
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
+fn lookup<'a>(comments: &[(&str, &'a str)], path: &str) -> Option<&'a str> {
+    comments
+        .iter()
+        .find(|(key, _)| *key == path)
+        .map(|(_, comment)| *comment)
+}
+
+fn unquote(key: &str) -> &str {
+    let key = key.trim();
+    key.strip_prefix('"')
+        .and_then(|k| k.strip_suffix('"'))
+        .unwrap_or(key)
+}
+
+#[cfg(any(feature = "toml_conf", test))]
+fn inject_toml(s: &str, comments: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut table = String::new();
+    let mut multiline: Option<&str> = None;
+
+    for line in s.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let indent = &line[..line.len() - line.trim_start().len()];
+
+        if let Some(delimiter) = multiline {
+            if trimmed.matches(delimiter).count() % 2 == 1 {
+                multiline = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+
+        let header = trimmed
+            .strip_prefix("[[")
+            .and_then(|t| t.strip_suffix("]]"))
+            .or_else(|| trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')));
+        if let Some(header) = header {
+            table = header.split('.').map(unquote).collect::<Vec<_>>().join(".");
+            if let Some(comment) = lookup(comments, &table) {
+                push_comment(&mut out, indent, comment);
+            }
+        } else if let Some((key, value)) = trimmed.split_once(" = ") {
+            let path = if table.is_empty() {
+                unquote(key).to_string()
+            } else {
+                format!("{}.{}", table, unquote(key))
+            };
+            if let Some(comment) = lookup(comments, &path) {
+                push_comment(&mut out, indent, comment);
+            }
+            for delimiter in &["\"\"\"", "'''"] {
+                if value.matches(delimiter).count() % 2 == 1 {
+                    multiline = Some(delimiter);
+                }
+            }
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+#[cfg(any(feature = "yaml_conf", test))]
+fn inject_yaml(s: &str, comments: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut block_scalar: Option<usize> = None;
+
+    for line in s.split_inclusive('\n') {
+        let content = line.trim_start();
+        let depth = line.len() - content.len();
+        let indent = &line[..depth];
+
+        if let Some(parent) = block_scalar {
+            if content.trim().is_empty() || depth > parent {
+                out.push_str(line);
+                continue;
+            }
+            block_scalar = None;
+        }
+
+        // Keys nested in sequences are not supported.
+        if !content.starts_with("- ") && !content.starts_with('#') {
+            let entry = content
+                .split_once(": ")
+                .or_else(|| content.trim_end().strip_suffix(':').map(|k| (k, "")));
+            if let Some((key, value)) = entry {
+                while matches!(stack.last(), Some((d, _)) if *d >= depth) {
+                    stack.pop();
+                }
+                stack.push((depth, unquote(key).to_string()));
+                let path = stack
+                    .iter()
+                    .map(|(_, k)| k.as_str())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                if let Some(comment) = lookup(comments, &path) {
+                    push_comment(&mut out, indent, comment);
+                }
+                if value.trim_start().starts_with(['|', '>']) {
+                    block_scalar = Some(depth);
+                }
+            }
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_comments() {
+        let input = "name = \"x\"\nnotes = '''\nport = 1\n'''\n\n[server]\nport = 80\n";
+        let comments = [
+            ("name", "The name"),
+            ("port", "Never used"),
+            ("server", "Server settings"),
+            ("server.port", "Port to bind"),
+            ("missing", "Skipped"),
+        ];
+        assert_eq!(
+            inject_toml(input, &comments),
+            "# The name\nname = \"x\"\nnotes = '''\nport = 1\n'''\n\n\
+# Server settings\n[server]\n# Port to bind\nport = 80\n"
+        );
+    }
+
+    #[test]
+    fn yaml_comments() {
+        let input = "name: x\nnotes: |-\n  port: 1\nserver:\n  port: 80\nlist:\n- a\n";
+        let comments = [
+            ("name", "The name"),
+            ("port", "Never used"),
+            ("server", "Server settings"),
+            ("server.port", "Port to bind"),
+        ];
+        assert_eq!(
+            inject_yaml(input, &comments),
+            "# The name\nname: x\nnotes: |-\n  port: 1\n\
+# Server settings\nserver:\n  # Port to bind\n  port: 80\nlist:\n- a\n"
+        );
+    }
+}
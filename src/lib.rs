@@ -51,7 +51,7 @@
 //! }
 //!
 //! fn main() -> Result<(), confy::ConfyError> {
-//!     let cfg = confy::load("my-app-name", None)?;
+//!     let cfg: MyConfig = confy::load("my-app-name", None)?;
 //!     Ok(())
 //! }
 //! ```
@@ -64,11 +64,42 @@
 mod utils;
 use utils::*;
 
+mod builder;
+pub use builder::{Config, ConfyBuilder, ConfyHandle};
+mod bundle;
+mod codec;
+pub use codec::Codec;
+#[cfg(feature = "toml_conf")]
+pub use codec::TomlCodec;
+#[cfg(feature = "yaml_conf")]
+pub use codec::YamlCodec;
+mod comments;
+#[cfg(feature = "memory")]
+mod memory;
+#[cfg(feature = "memory")]
+pub use memory::{clear_mem, load_mem, store_mem};
+mod sha256;
+mod source;
+pub use source::Source;
+mod strategy;
+pub use strategy::{PathStrategy, ProjectDirsStrategy};
+mod template;
+mod value;
+pub use value::Value;
+
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::{watch, watch_path, Watcher};
+
 use directories_next::ProjectDirs;
 use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions, Permissions};
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
 #[cfg(not(any(feature = "toml_conf", feature = "yaml_conf")))]
@@ -86,11 +117,51 @@ NOTE: `toml_conf` is a default feature, so disabling it might mean switching off
 default features for confy in your Cargo.toml"
 );
 
+/// A configuration file format supported by confy
+///
+/// Only the format of the enabled config language feature can be loaded and
+/// stored, but file names can be built for either, see [`extension_for`].
+///
+/// [`extension_for`]: fn.extension_for.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// TOML, enabled by the default `toml_conf` feature.
+    Toml,
+    /// YAML, enabled by the `yaml_conf` feature.
+    Yaml,
+}
+
+/// Get the file extension confy uses for a configuration format, without
+/// the leading dot
+///
+/// ```rust
+/// assert_eq!(confy::extension_for(confy::Format::Toml), "toml");
+/// assert_eq!(confy::extension_for(confy::Format::Yaml), "yml");
+/// ```
+pub const fn extension_for(format: Format) -> &'static str {
+    match format {
+        Format::Toml => "toml",
+        Format::Yaml => "yml",
+    }
+}
+
+#[cfg(feature = "toml_conf")]
+const EXTENSION: &str = extension_for(Format::Toml);
+
+#[cfg(feature = "yaml_conf")]
+const EXTENSION: &str = extension_for(Format::Yaml);
+
+/// The extensions accepted by [`load_path_any_ext`], in order of preference.
+///
+/// [`load_path_any_ext`]: fn.load_path_any_ext.html
 #[cfg(feature = "toml_conf")]
-const EXTENSION: &str = "toml";
+const EXTENSION_ALIASES: &[&str] = &["toml", "TOML", "Toml"];
 
 #[cfg(feature = "yaml_conf")]
-const EXTENSION: &str = "yml";
+const EXTENSION_ALIASES: &[&str] = &["yml", "yaml", "YML", "YAML", "Yml", "Yaml"];
+
+/// The configuration name used when none is given.
+pub const DEFAULT_CONFIG_NAME: &str = "default-config";
 
 /// The errors the confy crate can encounter.
 #[derive(Debug, Error)]
@@ -113,7 +184,7 @@ pub enum ConfyError {
     BadConfigDirectory(String),
 
     #[cfg(feature = "toml_conf")]
-    #[error("Failed to serialize configuration data into TOML")]
+    #[error("Failed to serialize configuration data into TOML{}", toml_ser_hint(.0))]
     SerializeTomlError(#[source] toml::ser::Error),
 
     #[cfg(feature = "yaml_conf")]
@@ -131,15 +202,336 @@ pub enum ConfyError {
 
     #[error("Failed to set configuration file permissions")]
     SetPermissionsFileError(#[source] std::io::Error),
+
+    #[error("Refusing to load configuration: {0:?} is group or world writable")]
+    InsecurePermissions(PathBuf),
+
+    #[error("Configuration validation failed: {0}")]
+    ValidationFailed(String),
+
+    #[error("Bad path template: {0}")]
+    BadPathTemplate(String),
+
+    #[error("Configuration path {0:?} is a directory")]
+    PathIsDirectory(PathBuf),
+
+    #[error("Bad configuration bundle: {0}")]
+    BadBundle(String),
+
+    #[error("Configuration file {0:?} is not valid UTF-8")]
+    NonUtf8Config(PathBuf),
+
+    #[error("Unknown configuration fields: {}", .0.join(", "))]
+    UnknownFields(Vec<String>),
+
+    #[error("Configuration is missing the field `{0}`")]
+    MissingField(String),
+
+    #[error("Failed to run editor: {0}")]
+    EditorError(String),
+
+    #[error("Configuration file {0:?} does not match its checksum, it may be corrupted")]
+    ChecksumMismatch(PathBuf),
+
+    #[error("Configuration location {0:?} is read-only")]
+    ReadOnlyConfigLocation(PathBuf),
+
+    #[error("Configuration file {0:?} is in use by another process")]
+    ConfigFileBusy(PathBuf),
+}
+
+impl ConfyError {
+    /// Whether the error is caused by the environment, e.g. a missing or
+    /// read-only file or directory, rather than by the configuration data.
+    pub fn is_io(&self) -> bool {
+        match self {
+            ConfyError::DirectoryCreationFailed(_)
+            | ConfyError::GeneralLoadError(_)
+            | ConfyError::BadConfigDirectory(_)
+            | ConfyError::WriteConfigurationFileError(_)
+            | ConfyError::ReadConfigurationFileError(_)
+            | ConfyError::OpenConfigurationFileError(_)
+            | ConfyError::SetPermissionsFileError(_)
+            | ConfyError::InsecurePermissions(_)
+            | ConfyError::PathIsDirectory(_)
+            | ConfyError::EditorError(_)
+            | ConfyError::ReadOnlyConfigLocation(_)
+            | ConfyError::ConfigFileBusy(_) => true,
+            #[cfg(feature = "toml_conf")]
+            ConfyError::BadTomlData(_) | ConfyError::SerializeTomlError(_) => false,
+            #[cfg(feature = "yaml_conf")]
+            ConfyError::BadYamlData(_) | ConfyError::SerializeYamlError(_) => false,
+            ConfyError::ValidationFailed(_)
+            | ConfyError::BadPathTemplate(_)
+            | ConfyError::BadBundle(_)
+            | ConfyError::NonUtf8Config(_)
+            | ConfyError::UnknownFields(_)
+            | ConfyError::MissingField(_)
+            | ConfyError::ChecksumMismatch(_) => false,
+        }
+    }
+
+    /// Whether the error is caused by the configuration data, e.g. a file
+    /// that does not parse, or a value that cannot be serialized.
+    ///
+    /// Errors in arguments, like [`ConfyError::BadPathTemplate`], are
+    /// neither data nor IO errors.
+    ///
+    /// [`ConfyError::BadPathTemplate`]: enum.ConfyError.html#variant.BadPathTemplate
+    pub fn is_data(&self) -> bool {
+        !self.is_io() && !matches!(self, ConfyError::BadPathTemplate(_))
+    }
+}
+
+/// A hint on the TOML limitation most likely behind a serialization error.
+#[cfg(feature = "toml_conf")]
+fn toml_ser_hint(e: &toml::ser::Error) -> &'static str {
+    match e {
+        toml::ser::Error::UnsupportedType => {
+            ": TOML requires a table at the top level, and cannot represent tuples or \
+             unit values; did you try to serialize a sequence or a non-struct type?"
+        }
+        toml::ser::Error::KeyNotString => {
+            ": TOML table keys must be strings; did you use a map with non-string keys?"
+        }
+        toml::ser::Error::ValueAfterTable => {
+            ": TOML requires plain values before tables; declare fields holding \
+             structs or maps after the other fields"
+        }
+        toml::ser::Error::UnsupportedNone => {
+            ": TOML has no null value; did you serialize a `None` outside of a struct field?"
+        }
+        _ => "",
+    }
+}
+
+/// Options used when loading a configuration with [`load_with_options`].
+///
+/// [`load_with_options`]: fn.load_with_options.html
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Override the file extension, which otherwise defaults to the one of
+    /// the enabled config language (`toml` or `yml`).
+    pub extension: Option<String>,
+
+    /// Leniently accept YAML files indented with tabs.  If parsing fails
+    /// because of a tab and the file has lines indented with tabs, those
+    /// tabs are expanded to tab stops of this width and parsing is retried
+    /// once.
+    #[cfg(feature = "yaml_conf")]
+    pub yaml_tab_width: Option<usize>,
+
+    /// Create the configuration directory when writing a default
+    /// configuration, which is the default.  If disabled and the directory
+    /// does not exist, [`ConfyError::BadConfigDirectory`] is returned
+    /// without attempting to create it, e.g. for locked-down environments,
+    /// like with [`StoreOptions::create_missing_dirs`].
+    ///
+    /// Only [`load_path_reporting_with_options`] writes a default
+    /// configuration and reads this; [`load_with_options`] and
+    /// [`load_path_with_options`] never write anything.
+    ///
+    /// [`ConfyError::BadConfigDirectory`]: enum.ConfyError.html#variant.BadConfigDirectory
+    /// [`StoreOptions::create_missing_dirs`]: struct.StoreOptions.html#structfield.create_missing_dirs
+    /// [`load_path_reporting_with_options`]: fn.load_path_reporting_with_options.html
+    /// [`load_with_options`]: fn.load_with_options.html
+    /// [`load_path_with_options`]: fn.load_path_with_options.html
+    pub create_missing_dirs: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            extension: None,
+            #[cfg(feature = "yaml_conf")]
+            yaml_tab_width: None,
+            create_missing_dirs: true,
+        }
+    }
+}
+
+/// Options used when storing a configuration with [`store_with_options`].
+///
+/// [`store_with_options`]: fn.store_with_options.html
+#[derive(Debug, Clone)]
+pub struct StoreOptions {
+    /// Override the file extension, which otherwise defaults to the one of
+    /// the enabled config language (`toml` or `yml`).
+    pub extension: Option<String>,
+
+    /// Text transformations applied to the serialized configuration before
+    /// it is written.
+    pub text: TextNormalization,
+
+    /// Pretty print the configuration, which is the default.  Disabling it
+    /// produces more compact TOML; the YAML output is the same either way.
+    pub pretty: bool,
+
+    /// Remove temporary files left behind by interrupted stores before
+    /// writing, see [`cleanup_temp_files`].
+    ///
+    /// [`cleanup_temp_files`]: fn.cleanup_temp_files.html
+    pub cleanup_temp_files: bool,
+
+    /// Make sure the configuration survives a crash or power loss once the
+    /// store returns, by syncing the file and its directory to disk.
+    ///
+    /// This is off by default, as syncing can be slow.  On platforms that
+    /// cannot sync directories, only the file is synced.
+    pub durable: bool,
+
+    /// How often to retry moving the written configuration into place if
+    /// that fails because the file is locked or access is denied, e.g. as a
+    /// virus scanner briefly locks the file on Windows.  Other errors are
+    /// returned right away.  Defaults to no retries.
+    pub rename_retries: u32,
+
+    /// How long to wait before each retry of [`rename_retries`], doubling
+    /// after every attempt.
+    ///
+    /// [`rename_retries`]: #structfield.rename_retries
+    pub retry_delay: Duration,
+
+    /// If the configuration file is a symbolic link, write to the file it
+    /// points to instead of replacing the link, e.g. for configurations
+    /// managed in a dotfiles repository.  On by default.
+    pub follow_symlinks: bool,
+
+    /// Write the configuration to a temporary file first, and move it over
+    /// the configuration file once complete, so that the file is never left
+    /// half-written.  On by default.
+    ///
+    /// Turn this off for file systems that do not support renaming files.
+    /// The configuration file is then truncated and written in place, so a
+    /// crash or a failing write can leave it corrupted.
+    pub atomic: bool,
+
+    /// Sort the keys of all tables alphabetically, instead of writing them
+    /// in the order of the struct fields, so that reordering fields in code
+    /// does not change the file.  Off by default.  This only affects TOML.
+    pub sort_keys: bool,
+
+    /// The suffix of the temporary files written before being moved over the
+    /// configuration file, `.tmp` by default, e.g. to match an ignore
+    /// pattern of a sync tool.  They are named `<stem>.<pid>_<random><suffix>`.
+    pub temp_suffix: Option<String>,
+
+    /// Create the configuration directory if it does not exist, which is the
+    /// default.  If disabled, a missing directory is reported as
+    /// [`ConfyError::BadConfigDirectory`], e.g. for directories that must be
+    /// set up by a package manager.
+    ///
+    /// [`ConfyError::BadConfigDirectory`]: enum.ConfyError.html#variant.BadConfigDirectory
+    pub create_missing_dirs: bool,
+
+    /// Write the file through a buffer of this many bytes, in chunks of at
+    /// most that size, instead of in one go, e.g. for very large
+    /// configurations.  `None`, the default, writes unbuffered.
+    pub write_buffer_size: Option<usize>,
+}
+
+impl Default for StoreOptions {
+    fn default() -> Self {
+        StoreOptions {
+            extension: None,
+            text: TextNormalization::default(),
+            pretty: true,
+            cleanup_temp_files: false,
+            durable: false,
+            rename_retries: 0,
+            retry_delay: Duration::from_millis(50),
+            follow_symlinks: true,
+            atomic: true,
+            sort_keys: false,
+            temp_suffix: None,
+            create_missing_dirs: true,
+            write_buffer_size: None,
+        }
+    }
+}
+
+/// Line ending used when writing a configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r\n` on Windows, `\n` everywhere else
+    Native,
+}
+
+/// Post-serialization text transformations, see [`StoreOptions`].
+///
+/// The default leaves the serialized text untouched, which always ends with
+/// a single newline already.  All transformations compose: lines are first
+/// stripped of trailing whitespace, then the trailing newline is fixed up,
+/// and finally line endings are converted.
+///
+/// [`StoreOptions`]: struct.StoreOptions.html
+#[derive(Debug, Clone, Default)]
+pub struct TextNormalization {
+    /// Line ending to use.  `None` keeps the serializer's `\n`.
+    pub line_ending: Option<LineEnding>,
+
+    /// Make sure the file ends with exactly one line ending.  Serialized
+    /// configurations already do, so this only makes a difference for YAML
+    /// ending in blank lines, e.g. after a `|+` block scalar, or for lines
+    /// left blank by [`trim_trailing_whitespace`].
+    ///
+    /// [`trim_trailing_whitespace`]: #structfield.trim_trailing_whitespace
+    pub trailing_newline: bool,
+
+    /// Strip trailing whitespace from every line.  Note that this also
+    /// applies to the content of multi-line strings.
+    pub trim_trailing_whitespace: bool,
+}
+
+impl TextNormalization {
+    fn apply(&self, s: String) -> String {
+        if self.line_ending.is_none() && !self.trailing_newline && !self.trim_trailing_whitespace {
+            return s;
+        }
+
+        let eol = match self.line_ending {
+            Some(LineEnding::CrLf) => "\r\n",
+            Some(LineEnding::Native) if cfg!(windows) => "\r\n",
+            _ => "\n",
+        };
+
+        let mut lines: Vec<&str> = s
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .map(|line| {
+                if self.trim_trailing_whitespace {
+                    line.trim_end()
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        if self.trailing_newline {
+            while lines.last() == Some(&"") {
+                lines.pop();
+            }
+            lines.push("");
+        }
+
+        lines.join(eol)
+    }
 }
 
 /// Load an application configuration from disk
 ///
-/// A new configuration file is created with default values if none
-/// exists.
+/// If the configuration file does not exist,
+/// [`ConfyError::GeneralLoadError`] is returned and nothing is written; use
+/// [`load_reporting`] to create it with default values instead.  An empty
+/// file, e.g. left behind by a crash, is loaded as the default values, but
+/// not overwritten.
 ///
 /// Errors that are returned from this function are I/O related,
-/// for example if the writing of the new configuration fails
+/// for example if reading the configuration fails
 /// or `confy` encounters an operating system or environment
 /// that it does not support.
 ///
@@ -158,6 +550,9 @@ pub enum ConfyError {
 /// # Ok(())
 /// # }
 /// ```
+///
+/// [`ConfyError::GeneralLoadError`]: enum.ConfyError.html#variant.GeneralLoadError
+/// [`load_reporting`]: fn.load_reporting.html
 pub fn load<'a, T: Serialize + DeserializeOwned + Default>(
     app_name: &str,
     config_name: impl Into<Option<&'a str>>,
@@ -165,10 +560,86 @@ pub fn load<'a, T: Serialize + DeserializeOwned + Default>(
     get_configuration_file_path(app_name, config_name).and_then(load_path)
 }
 
+/// Load an application configuration from disk, with additional options
+///
+/// This is an alternate version of [`load`] that takes a [`LoadOptions`]
+/// to tweak how the configuration file is located.  For more information
+/// on errors and behavior, see [`load`]'s documentation.
+///
+/// ```rust,no_run
+/// # use confy::{ConfyError, LoadOptions};
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// let options = LoadOptions {
+///     extension: Some("cfg".to_string()),
+///     ..LoadOptions::default()
+/// };
+/// let cfg: MyConfig = confy::load_with_options("my-app-name", "config", &options)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load`]: fn.load.html
+/// [`LoadOptions`]: struct.LoadOptions.html
+pub fn load_with_options<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    options: &LoadOptions,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path_with_extension(
+        app_name,
+        config_name,
+        options.extension.as_deref().unwrap_or(EXTENSION),
+    )
+    .and_then(|path| load_path_with_options(path, options))
+}
+
+/// Load an application configuration from a specified path, with additional
+/// options
+///
+/// This is an alternate version of [`load_path`] that takes a
+/// [`LoadOptions`].  As the path is given explicitly,
+/// [`LoadOptions::extension`] is ignored.  For more information on errors
+/// and behavior, see [`load_path`]'s documentation.
+///
+/// [`load_path`]: fn.load_path.html
+/// [`LoadOptions`]: struct.LoadOptions.html
+/// [`LoadOptions::extension`]: struct.LoadOptions.html#structfield.extension
+#[cfg_attr(not(feature = "yaml_conf"), allow(unused_variables))]
+pub fn load_path_with_options<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<T, ConfyError> {
+    read_config(
+        path.as_ref(),
+        || Ok(T::default()),
+        |cfg_string| {
+            let result = deserialize(cfg_string);
+
+            #[cfg(feature = "yaml_conf")]
+            if let (Err(ConfyError::BadYamlData(e)), Some(width)) =
+                (&result, options.yaml_tab_width)
+            {
+                if is_tab_error(&e.to_string()) && has_tab_indentation(cfg_string) {
+                    return parse_config_string(
+                        expand_leading_tabs(cfg_string, width),
+                        deserialize,
+                    );
+                }
+            }
+
+            result
+        },
+    )
+}
+
 /// Load an application configuration from a specified path.
 ///
-/// A new configuration file is created with default values if none
-/// exists.
+/// A missing file is an error, and an empty one is loaded as the default
+/// values, like with every load function of this crate.
 ///
 /// This is an alternate version of [`load`] that allows the specification of
 /// an arbitrary path instead of a system one.  For more information on errors
@@ -178,294 +649,5296 @@ pub fn load<'a, T: Serialize + DeserializeOwned + Default>(
 pub fn load_path<T: Serialize + DeserializeOwned + Default>(
     path: impl AsRef<Path>,
 ) -> Result<T, ConfyError> {
-    match File::open(&path) {
-        Ok(mut cfg) => {
-            let cfg_string = cfg
-                .get_string()
-                .map_err(ConfyError::ReadConfigurationFileError)?;
+    read_config(path.as_ref(), || Ok(T::default()), deserialize)
+}
 
-            #[cfg(feature = "toml_conf")]
-            {
-                let cfg_data = toml::from_str(&cfg_string);
-                cfg_data.map_err(ConfyError::BadTomlData)
-            }
-            #[cfg(feature = "yaml_conf")]
-            {
-                let cfg_data = serde_yaml::from_str(&cfg_string);
-                cfg_data.map_err(ConfyError::BadYamlData)
-            }
+/// Load an application configuration from a specified path without reading
+/// it into memory first
+///
+/// This is an alternate version of [`load_path`] for very large
+/// configuration files: with the `yaml_conf` feature, the file is
+/// deserialized straight from a buffered reader, avoiding a copy of its
+/// whole text.  TOML can only be parsed from a string, so with `toml_conf`
+/// this is the same as [`load_path`], as it is with the `secrets` feature,
+/// which needs the whole text to wipe it.
+///
+/// [`load_path`]: fn.load_path.html
+pub fn load_path_streaming<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, ConfyError> {
+    #[cfg(any(feature = "toml_conf", feature = "secrets"))]
+    {
+        load_path(path)
+    }
+    #[cfg(all(feature = "yaml_conf", not(feature = "secrets")))]
+    {
+        let path = path.as_ref();
+        if path.is_dir() {
+            return Err(ConfyError::PathIsDirectory(path.to_path_buf()));
         }
-        Err(e) => Err(ConfyError::GeneralLoadError(e)),
+        let file = File::open(path).map_err(ConfyError::GeneralLoadError)?;
+        // The same as `read_config` does for an empty file.
+        if file.metadata().map_err(ConfyError::GeneralLoadError)?.len() == 0 {
+            return Ok(T::default());
+        }
+        serde_yaml::from_reader(io::BufReader::new(file))
+            .map_err(|e| missing_field(&e.to_string()).unwrap_or(ConfyError::BadYamlData(e)))
     }
 }
 
-/// Save changes made to a configuration object
+/// Load an application configuration, change it, and store it again
 ///
-/// This function will update a configuration,
-/// with the provided values, and create a new one,
-/// if none exists.
-///
-/// You can also use this function to create a new configuration
-/// with different initial values than which are provided
-/// by your `Default` trait implementation, or if your
-/// configuration structure _can't_ implement `Default`.
+/// The configuration is loaded, falling back to default values if it does
+/// not exist, and passed to `f` to be changed.  If `f` returns `Ok`, the
+/// changed configuration is stored; if it returns `Err`, nothing is written,
+/// so partial changes are not persisted, and the error is returned.  Errors
+/// of confy are converted into the error type of `f`.
 ///
 /// ```rust,no_run
-/// # use serde_derive::{Serialize, Deserialize};
 /// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
 /// # fn main() -> Result<(), ConfyError> {
-/// #[derive(Serialize, Deserialize)]
-/// struct MyConf {}
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {
+///     volume: u8,
+/// }
 ///
-/// let my_cfg = MyConf {};
-/// confy::store("my-app-name", None, my_cfg)?;
+/// confy::with_config("my-app-name", None, |cfg: &mut MyConfig| {
+///     cfg.volume = cfg.volume.saturating_add(10);
+///     Ok::<_, ConfyError>(())
+/// })?;
 /// # Ok(())
 /// # }
 /// ```
-///
-/// Errors returned are I/O errors related to not being
-/// able to write the configuration file or if `confy`
-/// encounters an operating system or environment it does
-/// not support.
-pub fn store<'a, T: Serialize>(
+pub fn with_config<'a, T, R, E>(
     app_name: &str,
     config_name: impl Into<Option<&'a str>>,
-    cfg: T,
-) -> Result<(), ConfyError> {
+    f: impl FnOnce(&mut T) -> Result<R, E>,
+) -> Result<R, E>
+where
+    T: Serialize + DeserializeOwned + Default,
+    E: From<ConfyError>,
+{
     let path = get_configuration_file_path(app_name, config_name)?;
-    store_path(path, cfg)
+    with_config_path(path, f)
 }
 
-/// Save changes made to a configuration object at a specified path
+/// Load an application configuration from a specified path, change it, and
+/// store it again
 ///
-/// This is an alternate version of [`store`] that allows the specification of
-/// file permissions that must be set. For more information on errors and
-/// behavior, see [`store`]'s documentation.
+/// This is an alternate version of [`with_config`] that allows the
+/// specification of an arbitrary path instead of a system one.
 ///
-/// [`store`]: fn.store.html
-pub fn store_perms<'a, T: Serialize>(
+/// [`with_config`]: fn.with_config.html
+pub fn with_config_path<T, R, E>(
+    path: impl AsRef<Path>,
+    f: impl FnOnce(&mut T) -> Result<R, E>,
+) -> Result<R, E>
+where
+    T: Serialize + DeserializeOwned + Default,
+    E: From<ConfyError>,
+{
+    let path = path.as_ref();
+    let mut cfg = try_load_path(path)?.unwrap_or_default();
+    let result = f(&mut cfg)?;
+    store_path(path, &cfg)?;
+    Ok(result)
+}
+
+/// Load an application configuration from disk if it exists
+///
+/// This is an alternate version of [`load`] for probing configurations that
+/// may not exist: `Ok(None)` is returned if the file is missing, and nothing
+/// is written.  Errors reading or parsing an existing file are returned as
+/// usual.  An empty file is treated like a missing one, so that e.g.
+/// [`with_config`] uses the default values for it.
+///
+/// [`load`]: fn.load.html
+/// [`with_config`]: fn.with_config.html
+pub fn try_load<'a, T: DeserializeOwned>(
     app_name: &str,
     config_name: impl Into<Option<&'a str>>,
-    cfg: T,
-    perms: Permissions,
-) -> Result<(), ConfyError> {
-    let path = get_configuration_file_path(app_name, config_name)?;
-    store_path_perms(path, cfg, perms)
+) -> Result<Option<T>, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(try_load_path)
 }
 
-/// Save changes made to a configuration object at a specified path
+/// Load an application configuration from a specified path if it exists
 ///
-/// This is an alternate version of [`store`] that allows the specification of
-/// an arbitrary path instead of a system one.  For more information on errors
-/// and behavior, see [`store`]'s documentation.
+/// This is an alternate version of [`try_load`] that allows the
+/// specification of an arbitrary path instead of a system one.
 ///
-/// [`store`]: fn.store.html
-pub fn store_path<T: Serialize>(path: impl AsRef<Path>, cfg: T) -> Result<(), ConfyError> {
-    do_store(path.as_ref(), cfg, None)
+/// [`try_load`]: fn.try_load.html
+pub fn try_load_path<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<Option<T>, ConfyError> {
+    match read_config(path.as_ref(), || Ok(None), |s| deserialize(s).map(Some)) {
+        Err(ConfyError::GeneralLoadError(e)) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        result => result,
+    }
 }
 
-/// Save changes made to a configuration object at a specified path
+/// Load an application configuration from a specified path, rejecting
+/// unknown fields
 ///
-/// This is an alternate version of [`store_path`] that allows the
-/// specification of file permissions that must be set. For more information on
-/// errors and behavior, see [`store`]'s documentation.
+/// This is an alternate version of [`load_path`] that catches misspelled
+/// keys, which serde silently ignores by default, without requiring
+/// `#[serde(deny_unknown_fields)]` on the configuration type.  The file is
+/// deserialized, the configuration serialized again, and any key of the file
+/// missing from the result is reported, by its dotted path, as
+/// [`ConfyError::UnknownFields`].
 ///
-/// [`store_path`]: fn.store_path.html
-pub fn store_path_perms<T: Serialize>(
+/// [`load_path`]: fn.load_path.html
+/// [`ConfyError::UnknownFields`]: enum.ConfyError.html#variant.UnknownFields
+pub fn load_strict_path<T: Serialize + DeserializeOwned + Default>(
     path: impl AsRef<Path>,
-    cfg: T,
-    perms: Permissions,
-) -> Result<(), ConfyError> {
-    do_store(path.as_ref(), cfg, Some(perms))
-}
-
-fn do_store<T: Serialize>(
-    path: &Path,
-    cfg: T,
-    perms: Option<Permissions>,
-) -> Result<(), ConfyError> {
-    let config_dir = path
-        .parent()
-        .ok_or_else(|| ConfyError::BadConfigDirectory(format!("{:?} is a root or prefix", path)))?;
-    fs::create_dir_all(config_dir).map_err(ConfyError::DirectoryCreationFailed)?;
-
-    let s;
-    #[cfg(feature = "toml_conf")]
-    {
-        s = toml::to_string_pretty(&cfg).map_err(ConfyError::SerializeTomlError)?;
-    }
-    #[cfg(feature = "yaml_conf")]
-    {
-        s = serde_yaml::to_string(&cfg).map_err(ConfyError::SerializeYamlError)?;
+) -> Result<T, ConfyError> {
+    let file: Value = match read_config(path.as_ref(), || Ok(None), |s| deserialize(s).map(Some))? {
+        Some(file) => file,
+        None => return Ok(T::default()),
+    };
+    let cfg: T = value::from_value(file.clone())?;
+    let keys = unknown_fields(&file, &cfg)?;
+    if keys.is_empty() {
+        Ok(cfg)
+    } else {
+        Err(ConfyError::UnknownFields(keys))
     }
+}
 
-    let mut path_tmp = path.to_path_buf();
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let mut i = 0;
-    loop {
-        i += 1;
-        path_tmp.set_extension(format!(
-            "{}_{:?}_{}",
-            std::process::id(),
-            std::thread::current().id(),
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map(|x| x.as_nanos())
-                .unwrap_or(i)
-        ));
-        if !path_tmp.exists() {
-            break;
-        }
-    }
-    let mut f = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&path_tmp)
-        .map_err(ConfyError::OpenConfigurationFileError)?;
+/// A recoverable issue encountered by [`load_with_warnings`]
+///
+/// [`load_with_warnings`]: fn.load_with_warnings.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// The configuration file did not exist, and was created with default
+    /// values.
+    CreatedDefault,
 
-    if let Some(p) = perms {
-        f.set_permissions(p)
-            .map_err(ConfyError::SetPermissionsFileError)?;
-    }
+    /// The configuration file contains a key, given by its dotted path, that
+    /// is not part of the configuration type and was ignored.
+    UnknownField(String),
 
-    f.write_all(s.as_bytes())
-        .map_err(ConfyError::WriteConfigurationFileError)?;
-    f.flush().map_err(ConfyError::WriteConfigurationFileError)?;
-    drop(f);
-    std::fs::rename(path_tmp, path).map_err(ConfyError::WriteConfigurationFileError)?;
-    Ok(())
+    /// The configuration was migrated from the given older version.  confy
+    /// does not migrate configurations itself, this is for applications
+    /// reporting their own migrations alongside confy's warnings.
+    MigratedFrom(u32),
 }
 
-/// Get the configuration file path used by [`load`] and [`store`]
+/// Load an application configuration from disk, reporting recoverable issues
 ///
-/// This is useful if you want to show where the configuration file is to your user.
+/// This is an alternate version of [`load`] that returns the situations it
+/// silently recovers from as a list of [`Warning`]s, e.g. for logging:
+/// creating a default configuration because the file was missing, and
+/// ignoring unknown fields.  Hard errors are still returned as `Err`.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// let (cfg, warnings): (MyConfig, _) = confy::load_with_warnings("my-app-name", None)?;
+/// for warning in warnings {
+///     eprintln!("warning: {:?}", warning);
+/// }
+/// # Ok(())
+/// # }
+/// ```
 ///
 /// [`load`]: fn.load.html
-/// [`store`]: fn.store.html
-pub fn get_configuration_file_path<'a>(
+/// [`Warning`]: enum.Warning.html
+pub fn load_with_warnings<'a, T: Serialize + DeserializeOwned + Default>(
     app_name: &str,
     config_name: impl Into<Option<&'a str>>,
-) -> Result<PathBuf, ConfyError> {
-    let config_name = config_name.into().unwrap_or("default-config");
-    let project = ProjectDirs::from("rs", "", app_name).ok_or_else(|| {
-        ConfyError::BadConfigDirectory("could not determine home directory path".to_string())
-    })?;
-
-    let config_dir_str = get_configuration_directory_str(&project)?;
-
-    let path = [config_dir_str, &format!("{}.{}", config_name, EXTENSION)]
-        .iter()
-        .collect();
-
-    Ok(path)
+) -> Result<(T, Vec<Warning>), ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(load_path_with_warnings)
 }
 
-fn get_configuration_directory_str(project: &ProjectDirs) -> Result<&str, ConfyError> {
-    let path = project.config_dir();
-    path.to_str()
-        .ok_or_else(|| ConfyError::BadConfigDirectory(format!("{:?} is not valid Unicode", path)))
+/// Load an application configuration from a specified path, reporting
+/// recoverable issues
+///
+/// This is an alternate version of [`load_with_warnings`] that allows the
+/// specification of an arbitrary path instead of a system one.
+///
+/// [`load_with_warnings`]: fn.load_with_warnings.html
+pub fn load_path_with_warnings<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<(T, Vec<Warning>), ConfyError> {
+    let path = path.as_ref();
+    let file: Value = match read_config(path, || Ok(None), |s| deserialize(s).map(Some)) {
+        Ok(Some(file)) => file,
+        Ok(None) => return Ok((T::default(), Vec::new())),
+        Err(ConfyError::GeneralLoadError(e)) if e.kind() == io::ErrorKind::NotFound => {
+            let cfg = T::default();
+            store_path(path, &cfg)?;
+            return Ok((cfg, vec![Warning::CreatedDefault]));
+        }
+        Err(e) => return Err(e),
+    };
+    let cfg: T = value::from_value(file.clone())?;
+    let warnings = unknown_fields(&file, &cfg)?
+        .into_iter()
+        .map(Warning::UnknownField)
+        .collect();
+    Ok((cfg, warnings))
+}
+
+/// The dotted paths of the keys of `file` that are missing from `cfg`
+/// serialized again, i.e. that were ignored when deserializing.
+fn unknown_fields<T: Serialize>(file: &Value, cfg: &T) -> Result<Vec<String>, ConfyError> {
+    let (mut keys, mut known) = (Vec::new(), Vec::new());
+    value::leaves(file, "", &mut keys);
+    value::leaves(&value::to_value(cfg)?, "", &mut known);
+    keys.retain(|key| !known.contains(key));
+    Ok(keys)
+}
+
+/// Load an application configuration from a specified path, trying common
+/// variants of the extension
+///
+/// This is an alternate version of [`load_path`] for configuration files
+/// written by other tools.  `path` is given without extension, and the first
+/// existing file with a known extension for the enabled config language is
+/// loaded, e.g. `.toml` and `.TOML`, or `.yml` and `.yaml`.  If none exists,
+/// the configuration is loaded from the path with the regular extension.
+///
+/// [`load_path`]: fn.load_path.html
+pub fn load_path_any_ext<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, ConfyError> {
+    let with_extension = |extension: &str| {
+        let mut file = path.as_ref().as_os_str().to_owned();
+        file.push(".");
+        file.push(extension);
+        PathBuf::from(file)
+    };
+    let file = EXTENSION_ALIASES
+        .iter()
+        .map(|extension| with_extension(extension))
+        .find(|file| file.is_file())
+        .unwrap_or_else(|| with_extension(EXTENSION));
+    load_path(file)
+}
+
+fn read_config_string(path: &Path) -> Result<String, ConfyError> {
+    if path.is_dir() {
+        return Err(ConfyError::PathIsDirectory(path.to_path_buf()));
+    }
+    match File::open(path) {
+        Ok(mut cfg) => cfg.get_string().map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => ConfyError::NonUtf8Config(path.to_path_buf()),
+            _ => ConfyError::ReadConfigurationFileError(e),
+        }),
+        Err(e) => Err(ConfyError::GeneralLoadError(e)),
+    }
+}
+
+/// Read the configuration file at `path` and parse it with `parse`.  An
+/// empty file, e.g. left behind by a crash, is loaded with `default`
+/// instead, so that every load function treats it the same.
+fn read_config<T>(
+    path: &Path,
+    default: impl FnOnce() -> Result<T, ConfyError>,
+    parse: impl FnOnce(&str) -> Result<T, ConfyError>,
+) -> Result<T, ConfyError> {
+    parse_config_string(read_config_string(path)?, |cfg_string| {
+        if cfg_string.is_empty() {
+            default()
+        } else {
+            parse(cfg_string)
+        }
+    })
+}
+
+/// Parse the text of a configuration with `parse`, wiping the text
+/// afterwards with the `secrets` feature.
+fn parse_config_string<T>(
+    cfg_string: String,
+    parse: impl FnOnce(&str) -> Result<T, ConfyError>,
+) -> Result<T, ConfyError> {
+    let result = parse(&cfg_string);
+    wipe(&mut cfg_string.into_bytes());
+    result
+}
+
+/// Load a configuration of unknown shape from a specified path
+///
+/// This is an alternate version of [`load_path`] that returns the file as a
+/// dynamic [`Value`] tree instead of deserializing it into a type, e.g. to
+/// inspect the settings of a plugin.  Unlike [`load_path`], a missing file is
+/// always an error, as there is no default to fall back to.
+///
+/// [`load_path`]: fn.load_path.html
+/// [`Value`]: type.Value.html
+pub fn load_value_path(path: impl AsRef<Path>) -> Result<Value, ConfyError> {
+    parse_config_string(read_config_string(path.as_ref())?, deserialize)
+}
+
+/// Load an application configuration from a specified path in a custom
+/// format
+///
+/// This is an alternate version of [`load_path`] that deserializes the file
+/// with the given [`Codec`] instead of the configured language.  For more
+/// information on errors and behavior, see [`load_path`]'s documentation.
+///
+/// [`load_path`]: fn.load_path.html
+/// [`Codec`]: trait.Codec.html
+pub fn load_path_with_codec<T: DeserializeOwned, C: Codec>(
+    path: impl AsRef<Path>,
+    codec: &C,
+) -> Result<T, ConfyError> {
+    parse_config_string(read_config_string(path.as_ref())?, |s| codec.from_str(s))
+}
+
+/// Load an application configuration from disk, filling in missing fields
+/// from `Default`
+///
+/// This is an alternate version of [`load`] for configurations that gain
+/// new fields over time: fields and whole tables missing from the file, e.g.
+/// written by an older version of the application, are taken from
+/// `T::default()` without `#[serde(default)]` attributes.  See
+/// [`load_path_merging_defaults`] for details.
+///
+/// [`load`]: fn.load.html
+/// [`load_path_merging_defaults`]: fn.load_path_merging_defaults.html
+pub fn load_merging_defaults<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(load_path_merging_defaults)
+}
+
+/// Load an application configuration from a specified path, filling in
+/// missing fields from `Default`
+///
+/// This is an alternate version of [`load_merging_defaults`] that allows the
+/// specification of an arbitrary path instead of a system one.  It does not
+/// require `#[serde(default)]` on the fields of the configuration:
+/// `T::default()` is converted into a value tree, the configuration file is
+/// merged on top of it, and the result is deserialized.  Tables are merged
+/// recursively, so missing fields of nested structures are filled in as
+/// well.
+///
+/// [`load_merging_defaults`]: fn.load_merging_defaults.html
+pub fn load_path_merging_defaults<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, ConfyError> {
+    let mut merged = value::to_value(&T::default())?;
+    let file: value::Value =
+        match read_config(path.as_ref(), || Ok(None), |s| deserialize(s).map(Some))? {
+            Some(file) => file,
+            None => return Ok(T::default()),
+        };
+    value::merge(&mut merged, file);
+    value::from_value(merged)
+}
+
+/// Load an application configuration with drop-in overrides
+///
+/// This is an alternate version of [`load`] following the convention of
+/// e.g. systemd: next to `config.toml`, every `.toml` file in the directory
+/// `config.d` is merged over the configuration, in the lexical order of the
+/// file names, with later files winning.  Tables are merged recursively.
+///
+/// The configuration file and the drop-in directory are both optional;
+/// whatever is missing is filled in from the default values, like
+/// [`load_path_merging_defaults`] does.  Nothing is written.
+///
+/// [`load`]: fn.load.html
+/// [`load_path_merging_defaults`]: fn.load_path_merging_defaults.html
+pub fn load_with_dropins<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(load_path_with_dropins)
+}
+
+/// Load an application configuration from a specified path with drop-in
+/// overrides
+///
+/// This is an alternate version of [`load_with_dropins`] that allows the
+/// specification of an arbitrary path instead of a system one.  The drop-in
+/// directory is the path with its extension replaced by `d`.
+///
+/// [`load_with_dropins`]: fn.load_with_dropins.html
+pub fn load_path_with_dropins<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, ConfyError> {
+    let path = path.as_ref();
+    let mut merged = value::to_value(&T::default())?;
+    if let Some(file) = try_load_path::<Value>(path)? {
+        value::merge(&mut merged, file);
+    }
+
+    let mut dropins = match fs::read_dir(path.with_extension("d")) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ConfyError::GeneralLoadError)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(ConfyError::GeneralLoadError(e)),
+    };
+    dropins.retain(|dropin| dropin.is_file() && dropin.extension() == Some(EXTENSION.as_ref()));
+    dropins.sort();
+    for dropin in dropins {
+        let overlay: Value = parse_config_string(read_config_string(&dropin)?, deserialize)?;
+        value::merge(&mut merged, overlay);
+    }
+
+    value::from_value(merged)
+}
+
+/// Merge layers of configuration values and record where each value came
+/// from
+///
+/// `layers` are named value trees, e.g. defaults, a configuration file,
+/// environment variables and command line arguments, merged in order so
+/// that later layers win.  Tables are merged recursively.
+///
+/// Along with the merged configuration, a map from the dotted path of every
+/// value (e.g. `server.port`) to the name of the layer which supplied it is
+/// returned.  This helps answering why a setting has a certain value.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {
+///     port: u16,
+/// }
+///
+/// # let (file, env): (confy::Value, confy::Value) = unimplemented!();
+/// let (cfg, provenance): (MyConfig, _) =
+///     confy::load_with_provenance(&[("file", file), ("env", env)])?;
+/// println!("port comes from {}", provenance["port"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_with_provenance<T: DeserializeOwned>(
+    layers: &[(&str, Value)],
+) -> Result<(T, HashMap<String, String>), ConfyError> {
+    let mut merged = value::empty();
+    let mut provenance = HashMap::new();
+
+    for (name, layer) in layers {
+        let mut paths = Vec::new();
+        value::leaves(layer, "", &mut paths);
+        for path in paths {
+            // A value replaces whole tables, and tables replace values.
+            provenance.retain(|key: &String, _| {
+                !(key.starts_with(&path) && key[path.len()..].starts_with('.')
+                    || path.starts_with(key.as_str()) && path[key.len()..].starts_with('.'))
+            });
+            provenance.insert(path, name.to_string());
+        }
+        value::merge(&mut merged, layer.clone());
+    }
+
+    Ok((value::from_value(merged)?, provenance))
+}
+
+/// A configuration type which knows where it is stored
+///
+/// Implementing this trait allows loading and storing a configuration with
+/// [`load_typed`] and [`store_typed`], without repeating the application
+/// and configuration names at every call site.
+///
+/// ```rust,no_run
+/// # use confy::{ConfyConfig, ConfyError};
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// impl ConfyConfig for MyConfig {
+///     const APP_NAME: &'static str = "my-app-name";
+/// }
+///
+/// let cfg: MyConfig = confy::load_typed()?;
+/// confy::store_typed(&cfg)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load_typed`]: fn.load_typed.html
+/// [`store_typed`]: fn.store_typed.html
+pub trait ConfyConfig {
+    /// The application name, see [`load`].
+    ///
+    /// [`load`]: fn.load.html
+    const APP_NAME: &'static str;
+
+    /// The configuration name, see [`load`].
+    ///
+    /// [`load`]: fn.load.html
+    const CONFIG_NAME: &'static str = DEFAULT_CONFIG_NAME;
+
+    /// Get the configuration file path of this type.
+    fn configuration_file_path() -> Result<PathBuf, ConfyError> {
+        get_configuration_file_path(Self::APP_NAME, Self::CONFIG_NAME)
+    }
+}
+
+/// Load the configuration of a [`ConfyConfig`] type from disk
+///
+/// For more information on errors and behavior, see [`load`]'s
+/// documentation.
+///
+/// [`ConfyConfig`]: trait.ConfyConfig.html
+/// [`load`]: fn.load.html
+pub fn load_typed<T: ConfyConfig + Serialize + DeserializeOwned + Default>() -> Result<T, ConfyError>
+{
+    T::configuration_file_path().and_then(load_path)
+}
+
+/// Save the configuration of a [`ConfyConfig`] type to disk
+///
+/// For more information on errors and behavior, see [`store`]'s
+/// documentation.
+///
+/// [`ConfyConfig`]: trait.ConfyConfig.html
+/// [`store`]: fn.store.html
+pub fn store_typed<T: ConfyConfig + Serialize>(cfg: &T) -> Result<(), ConfyError> {
+    store_path(T::configuration_file_path()?, cfg)
+}
+
+/// The result of [`load_reporting`].
+///
+/// [`load_reporting`]: fn.load_reporting.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadOutcome<T> {
+    /// The loaded configuration.
+    pub config: T,
+
+    /// Whether the configuration file did not exist, and was created with
+    /// default values.
+    pub created: bool,
+}
+
+/// Load an application configuration from disk, reporting whether it was
+/// created
+///
+/// This is an alternate version of [`load`] which creates the configuration
+/// file with default values if none exists, and reports whether it did so.
+/// This allows e.g. greeting the user on the first run.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// let outcome = confy::load_reporting::<MyConfig>("my-app-name", None)?;
+/// if outcome.created {
+///     let path = confy::get_configuration_file_path("my-app-name", None)?;
+///     println!("Created a new configuration at {:?}", path);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load`]: fn.load.html
+pub fn load_reporting<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<LoadOutcome<T>, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(load_path_reporting)
+}
+
+/// Open an application configuration in the user's editor
+///
+/// The configuration file is created with default values if it does not
+/// exist, and opened in the editor named by `$VISUAL` or `$EDITOR`, falling
+/// back to `vi`, or `notepad` on Windows.  Once the editor exits, the file is
+/// loaded again to make sure the user did not break it, returning e.g.
+/// [`ConfyError::BadTomlData`] if it no longer parses.  If the editor cannot
+/// be run or fails, [`ConfyError::EditorError`] is returned.
+///
+/// [`ConfyError::BadTomlData`]: enum.ConfyError.html#variant.BadTomlData
+/// [`ConfyError::EditorError`]: enum.ConfyError.html#variant.EditorError
+pub fn edit<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(edit_path)
+}
+
+/// Open an application configuration at a specified path in the user's
+/// editor
+///
+/// This is an alternate version of [`edit`] that allows the specification of
+/// an arbitrary path instead of a system one.
+///
+/// [`edit`]: fn.edit.html
+pub fn edit_path<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, ConfyError> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .find(|editor| !editor.is_empty())
+        .and_then(|editor| editor.into_string().ok())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    edit_path_with(path.as_ref(), &editor)
+}
+
+fn edit_path_with<T: Serialize + DeserializeOwned + Default>(
+    path: &Path,
+    editor: &str,
+) -> Result<T, ConfyError> {
+    // A broken configuration is opened as well, so the user can fix it.
+    if !path.exists() {
+        store_path(path, T::default())?;
+    }
+
+    // The editor may come with arguments, e.g. `code --wait`.
+    let mut args = editor.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| ConfyError::EditorError("no editor given".to_string()))?;
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|e| ConfyError::EditorError(format!("{}: {}", program, e)))?;
+    if !status.success() {
+        return Err(ConfyError::EditorError(format!(
+            "{} exited with {}",
+            program, status
+        )));
+    }
+
+    load_path(path)
+}
+
+/// Load an application configuration from a specified path, reporting
+/// whether it was created
+///
+/// This is an alternate version of [`load_reporting`] that allows the
+/// specification of an arbitrary path instead of a system one.
+///
+/// [`load_reporting`]: fn.load_reporting.html
+pub fn load_path_reporting<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<LoadOutcome<T>, ConfyError> {
+    load_path_or_create(path.as_ref(), T::default, &LoadOptions::default())
+}
+
+/// Load an application configuration from a specified path, reporting
+/// whether it was created, with additional options
+///
+/// This is an alternate version of [`load_path_reporting`] that takes a
+/// [`LoadOptions`], e.g. to not create a missing configuration directory.
+/// As the path is given explicitly, [`LoadOptions::extension`] is ignored.
+///
+/// [`load_path_reporting`]: fn.load_path_reporting.html
+/// [`LoadOptions`]: struct.LoadOptions.html
+/// [`LoadOptions::extension`]: struct.LoadOptions.html#structfield.extension
+pub fn load_path_reporting_with_options<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<LoadOutcome<T>, ConfyError> {
+    load_path_or_create(path.as_ref(), T::default, options)
+}
+
+/// Load an application configuration from a specified path, creating it
+/// from a closure if missing
+///
+/// This is an alternate version of [`load_path`] for configuration types
+/// that do not implement `Default`: if the file does not exist, `default_fn`
+/// is called to produce the initial configuration, which is written to
+/// `path` and returned.  `default_fn` is not called otherwise.
+///
+/// [`load_path`]: fn.load_path.html
+pub fn load_or_else_path<T: Serialize + DeserializeOwned>(
+    path: impl AsRef<Path>,
+    default_fn: impl FnOnce() -> T,
+) -> Result<T, ConfyError> {
+    load_path_or_create(path.as_ref(), default_fn, &LoadOptions::default())
+        .map(|outcome| outcome.config)
+}
+
+/// Load the first existing of several application configurations
+///
+/// This is an alternate version of [`load`] for configurations that were
+/// renamed: the configurations `names` are tried in order, and the first
+/// one whose file exists is loaded.  If none exists, a configuration with
+/// default values is created under the first name.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// // Formerly known as "settings".
+/// let cfg: MyConfig = confy::load_first_of("my-app-name", &["config", "settings"])?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load`]: fn.load.html
+pub fn load_first_of<T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    names: &[&str],
+) -> Result<T, ConfyError> {
+    let paths = names
+        .iter()
+        .map(|name| get_configuration_file_path(app_name, *name))
+        .collect::<Result<Vec<_>, _>>()?;
+    load_path_first_of(&paths)
+}
+
+/// Load the first existing of several application configurations at
+/// specified paths
+///
+/// This is an alternate version of [`load_first_of`] that allows the
+/// specification of arbitrary paths instead of system ones.
+///
+/// [`load_first_of`]: fn.load_first_of.html
+pub fn load_path_first_of<T: Serialize + DeserializeOwned + Default, P: AsRef<Path>>(
+    paths: &[P],
+) -> Result<T, ConfyError> {
+    let first = paths
+        .first()
+        .ok_or_else(|| ConfyError::BadConfigDirectory("no configuration name given".to_string()))?;
+    match paths.iter().find(|path| path.as_ref().exists()) {
+        Some(path) => load_path(path),
+        None => load_path_or_create(first.as_ref(), T::default, &LoadOptions::default())
+            .map(|outcome| outcome.config),
+    }
+}
+
+/// Load an application configuration for a deployment environment
+///
+/// This is an alternate version of [`load_first_of`] for services with one
+/// configuration per environment, e.g. from an `APP_ENV` variable: the
+/// configuration `<base_name>.<env>` is loaded if it exists, falling back to
+/// the shared `<base_name>`.  If neither exists, a configuration with
+/// default values is created under the environment-specific name.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// let env = std::env::var("APP_ENV").unwrap_or_else(|_| "dev".to_string());
+/// // Loads e.g. `config.prod.toml`, or else `config.toml`.
+/// let cfg: MyConfig = confy::load_for_env("my-app-name", "config", &env)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load_first_of`]: fn.load_first_of.html
+pub fn load_for_env<T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    base_name: &str,
+    env: &str,
+) -> Result<T, ConfyError> {
+    let env_name = format!("{}.{}", base_name, env);
+    load_first_of(app_name, &[&env_name, base_name])
+}
+
+/// Load an application configuration from an explicit path if given, or
+/// from disk
+///
+/// This is a shorthand for the common command line option overriding the
+/// configuration file: [`load_path`] is used with `override_path` if it is
+/// `Some`, and [`load`] otherwise.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// // e.g. from `--config <path>`
+/// let override_path: Option<std::path::PathBuf> = None;
+/// let cfg: MyConfig = confy::load_or_path("my-app-name", None, override_path)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load_path`]: fn.load_path.html
+/// [`load`]: fn.load.html
+pub fn load_or_path<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    override_path: Option<impl AsRef<Path>>,
+) -> Result<T, ConfyError> {
+    match override_path {
+        Some(path) => load_path(path),
+        None => load(app_name, config_name),
+    }
+}
+
+fn load_path_or_create<T: Serialize + DeserializeOwned>(
+    path: &Path,
+    default_fn: impl FnOnce() -> T,
+    options: &LoadOptions,
+) -> Result<LoadOutcome<T>, ConfyError> {
+    match read_config(path, || Ok(None), |s| deserialize(s).map(Some)) {
+        Ok(config) => Ok(LoadOutcome {
+            config: config.unwrap_or_else(default_fn),
+            created: false,
+        }),
+        Err(ConfyError::GeneralLoadError(e)) if e.kind() == io::ErrorKind::NotFound => {
+            let config = default_fn();
+            let store_options = StoreOptions {
+                create_missing_dirs: options.create_missing_dirs,
+                ..StoreOptions::default()
+            };
+            do_store(path, &config, None, &store_options, fs_rename)?;
+            Ok(LoadOutcome {
+                config,
+                created: true,
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Load an application configuration from disk, in a given [`ConfigScope`]
+///
+/// This is an alternate version of [`load`] that resolves the path with
+/// [`get_configuration_file_path_scoped`].
+///
+/// [`ConfigScope`]: enum.ConfigScope.html
+/// [`load`]: fn.load.html
+/// [`get_configuration_file_path_scoped`]: fn.get_configuration_file_path_scoped.html
+pub fn load_scoped<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    scope: ConfigScope,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path_scoped(app_name, config_name, scope).and_then(load_path)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde::Serializer;
-    use serde_derive::{Deserialize, Serialize};
+/// Load an application configuration from disk, with a custom directory
+/// policy
+///
+/// This is an alternate version of [`load`] that resolves the path with
+/// [`get_configuration_file_path_with_strategy`].
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// // Follow the XDG convention on every platform.
+/// let xdg = |app_name: &str| {
+///     let home = std::env::var_os("HOME")?;
+///     Some(std::path::Path::new(&home).join(".config").join(app_name))
+/// };
+/// let cfg: MyConfig = confy::load_with_strategy(&xdg, "my-app-name", None)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load`]: fn.load.html
+/// [`get_configuration_file_path_with_strategy`]: fn.get_configuration_file_path_with_strategy.html
+pub fn load_with_strategy<'a, T: Serialize + DeserializeOwned + Default>(
+    strategy: &impl PathStrategy,
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path_with_strategy(strategy, app_name, config_name).and_then(load_path)
+}
+
+/// Load an application configuration from below an explicit base directory
+///
+/// This is an alternate version of [`load`] that resolves the path with
+/// [`get_configuration_file_path_in`] instead of the platform configuration
+/// directory.
+///
+/// [`load`]: fn.load.html
+/// [`get_configuration_file_path_in`]: fn.get_configuration_file_path_in.html
+pub fn load_in<'a, T: Serialize + DeserializeOwned + Default>(
+    base_dir: impl AsRef<Path>,
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<T, ConfyError> {
+    load_path(get_configuration_file_path_in(
+        base_dir,
+        app_name,
+        config_name,
+    ))
+}
+
+/// Load an application configuration from disk and validate it
+///
+/// This is an alternate version of [`load`] that runs `validate` on the
+/// loaded configuration.  This allows rejecting configurations that parse
+/// fine but are semantically invalid, e.g. a port set to `0`.  An error
+/// message returned by `validate` is wrapped into
+/// [`ConfyError::ValidationFailed`].
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {
+///     port: u16,
+/// }
+///
+/// let cfg: MyConfig = confy::load_validated("my-app-name", None, |cfg: &MyConfig| {
+///     if cfg.port == 0 {
+///         return Err("port must not be 0".to_string());
+///     }
+///     Ok(())
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load`]: fn.load.html
+/// [`ConfyError::ValidationFailed`]: enum.ConfyError.html#variant.ValidationFailed
+pub fn load_validated<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    validate: impl Fn(&T) -> Result<(), String>,
+) -> Result<T, ConfyError> {
+    let path = get_configuration_file_path(app_name, config_name)?;
+    load_path_validated(path, validate)
+}
+
+/// Load an application configuration from a specified path and validate it
+///
+/// This is an alternate version of [`load_validated`] that allows the
+/// specification of an arbitrary path instead of a system one.
+///
+/// [`load_validated`]: fn.load_validated.html
+pub fn load_path_validated<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+    validate: impl Fn(&T) -> Result<(), String>,
+) -> Result<T, ConfyError> {
+    let cfg = load_path(path)?;
+    validate(&cfg).map_err(ConfyError::ValidationFailed)?;
+    Ok(cfg)
+}
+
+/// Load an application configuration from disk, falling back to an embedded
+/// copy
+///
+/// This is an alternate version of [`load`] for self-contained
+/// applications.  If the configuration file can not be found or read, e.g.
+/// because the home directory is missing or unwritable, `embedded` is parsed
+/// instead.  Nothing is ever written to disk.  Errors from parsing an
+/// existing configuration file are still returned as is.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {
+///     name: String,
+/// }
+///
+/// let cfg: MyConfig =
+///     confy::load_with_embedded_fallback("my-app-name", None, "name = \"bundled\"")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`load`]: fn.load.html
+pub fn load_with_embedded_fallback<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    embedded: &str,
+) -> Result<T, ConfyError> {
+    match get_configuration_file_path(app_name, config_name) {
+        Ok(path) => load_path_with_embedded_fallback(path, embedded),
+        Err(_) => deserialize(embedded),
+    }
+}
+
+/// Load an application configuration from a specified path, falling back to
+/// an embedded copy
+///
+/// This is an alternate version of [`load_with_embedded_fallback`] that
+/// allows the specification of an arbitrary path instead of a system one.
+///
+/// [`load_with_embedded_fallback`]: fn.load_with_embedded_fallback.html
+pub fn load_path_with_embedded_fallback<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+    embedded: &str,
+) -> Result<T, ConfyError> {
+    match load_path(path) {
+        Err(ConfyError::GeneralLoadError(_)) | Err(ConfyError::ReadConfigurationFileError(_)) => {
+            deserialize(embedded)
+        }
+        result => result,
+    }
+}
+
+/// Load an application configuration, creating it from an embedded default
+///
+/// This is an alternate version of [`load`] for defaults that are easier to
+/// express as a configuration file than as a `Default` implementation, e.g.
+/// embedded with `include_str!`.  If the configuration file does not exist,
+/// `embedded` is parsed, written to disk as is, comments included, and
+/// returned.  As `embedded` is part of the application, failing to parse it
+/// is a bug, reported as e.g. [`ConfyError::BadTomlData`].
+///
+/// Unlike [`load_with_embedded_fallback`], any other error is returned.
+///
+/// [`load`]: fn.load.html
+/// [`load_with_embedded_fallback`]: fn.load_with_embedded_fallback.html
+/// [`ConfyError::BadTomlData`]: enum.ConfyError.html#variant.BadTomlData
+pub fn load_with_embedded_default<'a, T: Serialize + DeserializeOwned>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    embedded: &str,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path(app_name, config_name)
+        .and_then(|path| load_path_with_embedded_default(path, embedded))
+}
+
+/// Load an application configuration from a specified path, creating it
+/// from an embedded default
+///
+/// This is an alternate version of [`load_with_embedded_default`] that
+/// allows the specification of an arbitrary path instead of a system one.
+///
+/// [`load_with_embedded_default`]: fn.load_with_embedded_default.html
+pub fn load_path_with_embedded_default<T: Serialize + DeserializeOwned>(
+    path: impl AsRef<Path>,
+    embedded: &str,
+) -> Result<T, ConfyError> {
+    let path = path.as_ref();
+    match read_config(path, || deserialize(embedded), deserialize) {
+        Err(ConfyError::GeneralLoadError(e)) if e.kind() == io::ErrorKind::NotFound => {
+            let cfg = deserialize(embedded)?;
+            write_config(
+                path,
+                embedded.to_string(),
+                None,
+                &StoreOptions::default(),
+                fs_rename,
+            )?;
+            Ok(cfg)
+        }
+        result => result,
+    }
+}
+
+/// Load an application configuration from disk, refusing insecure files
+///
+/// This is an alternate version of [`load`] that, on Unix, refuses to load a
+/// configuration file which could have been tampered with by other users.
+/// If either the file or its directory is group or world writable,
+/// [`ConfyError::InsecurePermissions`] is returned before the file is parsed.
+/// On other platforms, this behaves exactly like [`load`].
+///
+/// [`load`]: fn.load.html
+/// [`ConfyError::InsecurePermissions`]: enum.ConfyError.html#variant.InsecurePermissions
+pub fn load_secure<'a, T: Serialize + DeserializeOwned + Default>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<T, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(load_path_secure)
+}
+
+/// Load an application configuration from a specified path, refusing
+/// insecure files
+///
+/// This is an alternate version of [`load_secure`] that allows the
+/// specification of an arbitrary path instead of a system one.
+///
+/// [`load_secure`]: fn.load_secure.html
+pub fn load_path_secure<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, ConfyError> {
+    #[cfg(unix)]
+    check_permissions(path.as_ref())?;
+    load_path(path)
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> Result<(), ConfyError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = path.parent().unwrap_or(path);
+    for p in &[dir, path] {
+        // Missing files are reported by the regular load logic.
+        if let Ok(metadata) = fs::metadata(p) {
+            if metadata.permissions().mode() & 0o022 != 0 {
+                return Err(ConfyError::InsecurePermissions(p.to_path_buf()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Save changes made to a configuration object
+///
+/// This function will update a configuration,
+/// with the provided values, and create a new one,
+/// if none exists.
+///
+/// You can also use this function to create a new configuration
+/// with different initial values than which are provided
+/// by your `Default` trait implementation, or if your
+/// configuration structure _can't_ implement `Default`.
+///
+/// ```rust,no_run
+/// # use serde_derive::{Serialize, Deserialize};
+/// # use confy::ConfyError;
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Serialize, Deserialize)]
+/// struct MyConf {}
+///
+/// let my_cfg = MyConf {};
+/// confy::store("my-app-name", None, &my_cfg)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// As `&T` implements `Serialize` whenever `T` does, the configuration can
+/// be passed by reference, like above, to keep using it afterwards without
+/// cloning it.  This holds for all store functions of this crate.
+///
+/// Errors returned are I/O errors related to not being
+/// able to write the configuration file or if `confy`
+/// encounters an operating system or environment it does
+/// not support.
+pub fn store<'a, T: Serialize>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    cfg: T,
+) -> Result<(), ConfyError> {
+    let path = get_configuration_file_path(app_name, config_name)?;
+    store_path(path, cfg)
+}
+
+/// Save changes made to a configuration object, in a given [`ConfigScope`]
+///
+/// This is an alternate version of [`store`] that resolves the path with
+/// [`get_configuration_file_path_scoped`].
+///
+/// [`ConfigScope`]: enum.ConfigScope.html
+/// [`store`]: fn.store.html
+/// [`get_configuration_file_path_scoped`]: fn.get_configuration_file_path_scoped.html
+pub fn store_scoped<'a, T: Serialize>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    cfg: T,
+    scope: ConfigScope,
+) -> Result<(), ConfyError> {
+    let path = get_configuration_file_path_scoped(app_name, config_name, scope)?;
+    store_path(path, cfg)
+}
+
+/// Save changes made to a configuration object, with a custom directory
+/// policy
+///
+/// This is an alternate version of [`store`] that resolves the path with
+/// [`get_configuration_file_path_with_strategy`].
+///
+/// [`store`]: fn.store.html
+/// [`get_configuration_file_path_with_strategy`]: fn.get_configuration_file_path_with_strategy.html
+pub fn store_with_strategy<'a, T: Serialize>(
+    strategy: &impl PathStrategy,
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    cfg: T,
+) -> Result<(), ConfyError> {
+    let path = get_configuration_file_path_with_strategy(strategy, app_name, config_name)?;
+    store_path(path, cfg)
+}
+
+/// Save changes made to a configuration object at an explicit path if
+/// given, or to disk
+///
+/// This is the counterpart of [`load_or_path`]: [`store_path`] is used with
+/// `override_path` if it is `Some`, and [`store`] otherwise.
+///
+/// [`load_or_path`]: fn.load_or_path.html
+/// [`store_path`]: fn.store_path.html
+/// [`store`]: fn.store.html
+pub fn store_or_path<'a, T: Serialize>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    override_path: Option<impl AsRef<Path>>,
+    cfg: T,
+) -> Result<(), ConfyError> {
+    match override_path {
+        Some(path) => store_path(path, cfg),
+        None => store(app_name, config_name, cfg),
+    }
+}
+
+/// Save changes made to a configuration object below an explicit base
+/// directory
+///
+/// This is an alternate version of [`store`] that resolves the path with
+/// [`get_configuration_file_path_in`] instead of the platform configuration
+/// directory.
+///
+/// [`store`]: fn.store.html
+/// [`get_configuration_file_path_in`]: fn.get_configuration_file_path_in.html
+pub fn store_in<'a, T: Serialize>(
+    base_dir: impl AsRef<Path>,
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    cfg: T,
+) -> Result<(), ConfyError> {
+    store_path(
+        get_configuration_file_path_in(base_dir, app_name, config_name),
+        cfg,
+    )
+}
+
+/// Load a configuration stored next to the executable
+///
+/// This is an alternate version of [`load`] for portable applications, e.g.
+/// run from a USB stick, that resolves the path with
+/// [`get_portable_config_path`] instead of the platform configuration
+/// directory.
+///
+/// [`load`]: fn.load.html
+/// [`get_portable_config_path`]: fn.get_portable_config_path.html
+pub fn load_portable<'a, T: Serialize + DeserializeOwned + Default>(
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<T, ConfyError> {
+    get_portable_config_path(config_name).and_then(load_path)
+}
+
+/// Save changes made to a configuration object next to the executable
+///
+/// This is an alternate version of [`store`] that resolves the path with
+/// [`get_portable_config_path`] instead of the platform configuration
+/// directory.
+///
+/// [`store`]: fn.store.html
+/// [`get_portable_config_path`]: fn.get_portable_config_path.html
+pub fn store_portable<'a, T: Serialize>(
+    config_name: impl Into<Option<&'a str>>,
+    cfg: T,
+) -> Result<(), ConfyError> {
+    get_portable_config_path(config_name).and_then(|path| store_path(path, cfg))
+}
+
+/// Save changes made to a configuration object, with additional options
+///
+/// This is an alternate version of [`store`] that takes a [`StoreOptions`]
+/// to tweak how the configuration file is written.  For more information
+/// on errors and behavior, see [`store`]'s documentation.
+///
+/// [`store`]: fn.store.html
+/// [`StoreOptions`]: struct.StoreOptions.html
+pub fn store_with_options<'a, T: Serialize>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    cfg: T,
+    options: &StoreOptions,
+) -> Result<(), ConfyError> {
+    let path = get_configuration_file_path_with_extension(
+        app_name,
+        config_name,
+        options.extension.as_deref().unwrap_or(EXTENSION),
+    )?;
+    store_path_with_options(path, cfg, options)
+}
+
+/// Save changes made to a configuration object at a specified path
+///
+/// This is an alternate version of [`store`] that allows the specification of
+/// file permissions that must be set. For more information on errors and
+/// behavior, see [`store`]'s documentation.
+///
+/// [`store`]: fn.store.html
+pub fn store_perms<'a, T: Serialize>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    cfg: T,
+    perms: Permissions,
+) -> Result<(), ConfyError> {
+    let path = get_configuration_file_path(app_name, config_name)?;
+    store_path_perms(path, cfg, perms)
+}
+
+/// Save changes made to a configuration object at a specified path
+///
+/// This is an alternate version of [`store`] that allows the specification of
+/// an arbitrary path instead of a system one.  For more information on errors
+/// and behavior, see [`store`]'s documentation.
+///
+/// [`store`]: fn.store.html
+pub fn store_path<T: Serialize>(path: impl AsRef<Path>, cfg: T) -> Result<(), ConfyError> {
+    do_store(
+        path.as_ref(),
+        cfg,
+        None,
+        &StoreOptions::default(),
+        fs_rename,
+    )
+}
+
+/// Save changes made to a configuration object at a specified path, with
+/// additional options
+///
+/// This is an alternate version of [`store_path`] that takes a
+/// [`StoreOptions`].  As the path is given explicitly,
+/// [`StoreOptions::extension`] is ignored.  For more information on errors
+/// and behavior, see [`store`]'s documentation.
+///
+/// [`store`]: fn.store.html
+/// [`store_path`]: fn.store_path.html
+/// [`StoreOptions`]: struct.StoreOptions.html
+/// [`StoreOptions::extension`]: struct.StoreOptions.html#structfield.extension
+pub fn store_path_with_options<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: T,
+    options: &StoreOptions,
+) -> Result<(), ConfyError> {
+    do_store(path.as_ref(), cfg, None, options, fs_rename)
+}
+
+/// Save a configuration of unknown shape at a specified path
+///
+/// This is the counterpart of [`load_value_path`], writing a dynamic
+/// [`Value`] tree atomically like [`store_path`] does.
+///
+/// [`load_value_path`]: fn.load_value_path.html
+/// [`store_path`]: fn.store_path.html
+/// [`Value`]: type.Value.html
+pub fn store_value_path(path: impl AsRef<Path>, value: &Value) -> Result<(), ConfyError> {
+    store_path(path, value)
+}
+
+/// Save several related configurations together
+///
+/// Each entry is a configuration name and its serialized content, as
+/// returned by [`store_preview`].  All configurations are first written to
+/// temporary files and synced to disk, and only then moved into place.  If
+/// anything fails before the files are moved, every configuration is left
+/// untouched.
+///
+/// This is not a true atomic commit across files: a crash while the files
+/// are being moved can still leave some of them updated and others not, but
+/// the window for this is very small.
+///
+/// [`store_preview`]: fn.store_preview.html
+pub fn store_many(app_name: &str, configs: &[(&str, String)]) -> Result<(), ConfyError> {
+    let configs = configs
+        .iter()
+        .map(|(config_name, s)| Ok((get_configuration_file_path(app_name, *config_name)?, s)))
+        .collect::<Result<Vec<_>, ConfyError>>()?;
+    store_many_path(&configs)
+}
+
+/// Save several related configurations together at specified paths
+///
+/// This is an alternate version of [`store_many`] that allows the
+/// specification of arbitrary paths instead of system ones.
+///
+/// [`store_many`]: fn.store_many.html
+pub fn store_many_path<P: AsRef<Path>, S: AsRef<str>>(
+    configs: &[(P, S)],
+) -> Result<(), ConfyError> {
+    let options = StoreOptions {
+        durable: true,
+        ..StoreOptions::default()
+    };
+    let paths = configs
+        .iter()
+        .map(|(path, _)| resolve_symlinks(path.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Temporary files written so far are removed when returning early.
+    let mut written = Vec::with_capacity(configs.len());
+    for (path, (_, s)) in paths.into_iter().zip(configs) {
+        let path_tmp = write_temp(&path, s.as_ref().to_string(), None, &options)?;
+        written.push((Some(path_tmp), path));
+    }
+
+    for (path_tmp, path) in &mut written {
+        if let Some(path_tmp) = path_tmp.take() {
+            fs::rename(&path_tmp, &path).map_err(ConfyError::WriteConfigurationFileError)?;
+            let _ = path_tmp.keep();
+            remove_stale_checksum(path)?;
+        }
+    }
+    for (_, path) in &written {
+        if let Some(config_dir) = path.parent() {
+            sync_dir(config_dir).map_err(ConfyError::WriteConfigurationFileError)?;
+        }
+    }
+    Ok(())
+}
+
+/// Save changes made to a configuration object at a specified path in a
+/// custom format
+///
+/// This is an alternate version of [`store_path`] that serializes the
+/// configuration with the given [`Codec`] instead of the configured
+/// language.  The file is still written atomically.  For more information on
+/// errors and behavior, see [`store`]'s documentation.
+///
+/// [`store`]: fn.store.html
+/// [`store_path`]: fn.store_path.html
+/// [`Codec`]: trait.Codec.html
+pub fn store_path_with_codec<T: Serialize, C: Codec>(
+    path: impl AsRef<Path>,
+    cfg: T,
+    codec: &C,
+) -> Result<(), ConfyError> {
+    let s = codec.to_string(&cfg)?;
+    write_config(path.as_ref(), s, None, &StoreOptions::default(), fs_rename)
+}
+
+/// Save changes made to a configuration object at a specified path
+///
+/// This is an alternate version of [`store_path`] that allows the
+/// specification of file permissions that must be set. For more information on
+/// errors and behavior, see [`store`]'s documentation.
+///
+/// [`store_path`]: fn.store_path.html
+pub fn store_path_perms<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: T,
+    perms: Permissions,
+) -> Result<(), ConfyError> {
+    do_store(
+        path.as_ref(),
+        cfg,
+        Some(perms),
+        &StoreOptions::default(),
+        fs_rename,
+    )
+}
+
+/// Save changes made to a configuration object at a path built from a
+/// template
+///
+/// This is an alternate version of [`store_path`] for rotated or per-host
+/// configuration snapshots.  The following placeholders are replaced in
+/// `template` to build the path the configuration is stored at:
+///
+/// * `{host}`: the host name
+/// * `{date}`: the current UTC date, as `YYYY-MM-DD`
+/// * `{pid}`: the current process id
+/// * `{config_ext}`: the extension of the enabled config language
+///
+/// Any other placeholder, or a host name that can not be determined when
+/// `{host}` is used, results in [`ConfyError::BadPathTemplate`].  The
+/// resulting path is returned.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// let path = confy::store_path_template(
+///     "/var/backups/my-app/config-{host}-{date}.{config_ext}",
+///     MyConfig::default(),
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`store_path`]: fn.store_path.html
+/// [`ConfyError::BadPathTemplate`]: enum.ConfyError.html#variant.BadPathTemplate
+pub fn store_path_template<T: Serialize>(template: &str, cfg: T) -> Result<PathBuf, ConfyError> {
+    let path = template::expand(template, &template::Placeholders::current())?;
+    store_path(&path, cfg)?;
+    Ok(path)
+}
+
+/// Save changes made to a configuration object at a specified path, if
+/// there are any
+///
+/// This is an alternate version of [`store_path`] that compares the
+/// serialized configuration with the current file content, and only writes
+/// it if they differ.  This avoids needlessly bumping the modification time
+/// of the file, e.g. when storing periodically.  Returns whether the file
+/// was written; it always is when it does not exist yet.
+///
+/// [`store_path`]: fn.store_path.html
+pub fn store_if_changed_path<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: T,
+) -> Result<bool, ConfyError> {
+    let path = path.as_ref();
+    let s = serialize(&cfg)?;
+    if fs::read(path).ok().as_deref() == Some(s.as_bytes()) {
+        return Ok(false);
+    }
+    write_config(path, s, None, &StoreOptions::default(), fs_rename)?;
+    Ok(true)
+}
+
+/// Save changes made to a configuration object at a specified path,
+/// returning the bytes written
+///
+/// This is an alternate version of [`store_path`] for auditing: the returned
+/// bytes are exactly the content of the file, e.g. to log a hash of it,
+/// without serializing the configuration a second time.
+///
+/// [`store_path`]: fn.store_path.html
+pub fn store_path_returning_bytes<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: T,
+) -> Result<Vec<u8>, ConfyError> {
+    let s = serialize(&cfg)?;
+    write_config(
+        path.as_ref(),
+        s.clone(),
+        None,
+        &StoreOptions::default(),
+        fs_rename,
+    )?;
+    Ok(s.into_bytes())
+}
+
+/// Save changes made to a configuration object at a specified path,
+/// returning the previous configuration
+///
+/// This is an alternate version of [`store_path`] that reads the existing
+/// configuration file before replacing it.  The previous configuration is
+/// returned, or `None` if there was no file or it could not be parsed.
+/// This is useful to offer undoing the last change.  For more information
+/// on errors and behavior, see [`store`]'s documentation.
+///
+/// [`store`]: fn.store.html
+/// [`store_path`]: fn.store_path.html
+pub fn store_path_returning_old<T: Serialize + DeserializeOwned>(
+    path: impl AsRef<Path>,
+    cfg: &T,
+) -> Result<Option<T>, ConfyError> {
+    let path = path.as_ref();
+    let old = read_config_string(path)
+        .and_then(|s| parse_config_string(s, deserialize))
+        .ok();
+    store_path(path, cfg)?;
+    Ok(old)
+}
+
+/// Save changes made to a configuration object at a specified path, using a
+/// custom rename step
+///
+/// Configurations are written to a temporary file first, which is then moved
+/// over the target path, so that the target is never left half-written.
+/// This is an alternate version of [`store_path`] for filesystems where
+/// [`std::fs::rename`] is not atomic but another mechanism is available:
+/// `rename` is called with the temporary file and the target path instead
+/// of `std::fs::rename`.  It is called again if it fails and
+/// [`StoreOptions::rename_retries`] allow it.  For more information on
+/// errors and behavior, see
+/// [`store`]'s documentation.
+///
+/// [`store`]: fn.store.html
+/// [`store_path`]: fn.store_path.html
+/// [`std::fs::rename`]: https://doc.rust-lang.org/std/fs/fn.rename.html
+/// [`StoreOptions::rename_retries`]: struct.StoreOptions.html#structfield.rename_retries
+pub fn store_path_with_rename<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: T,
+    rename: impl FnMut(&Path, &Path) -> io::Result<()>,
+) -> Result<(), ConfyError> {
+    do_store(path.as_ref(), cfg, None, &StoreOptions::default(), rename)
+}
+
+/// Save changes made to a configuration object at a specified path, keeping
+/// numbered previous versions
+///
+/// This is an alternate version of [`store_path`] that keeps up to `keep`
+/// previous versions of the file for manual rollback: before storing
+/// `config.toml`, `config.1.toml` is moved to `config.2.toml` and so on,
+/// the current file is copied to `config.1.toml`, and the version numbered
+/// `keep` is dropped.  Missing versions are skipped.
+///
+/// Nothing is rotated if the configuration cannot be serialized, or the new
+/// version cannot be written, e.g. because a store guard vetoes it.
+///
+/// [`store_path`]: fn.store_path.html
+pub fn store_path_rotating<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: T,
+    keep: usize,
+) -> Result<(), ConfyError> {
+    let path = path.as_ref();
+    let s = serialize(&cfg)?;
+    write_config_rotating(path, s, None, &StoreOptions::default(), fs_rename, keep)
+}
+
+/// Shift the numbered versions of `path` up by one, see
+/// [`store_path_rotating`].
+///
+/// [`store_path_rotating`]: fn.store_path_rotating.html
+fn rotate_versions(path: &Path, keep: usize) -> Result<(), ConfyError> {
+    if keep > 0 && path.is_file() {
+        let version = |n: usize| {
+            let mut name = path.file_stem().unwrap_or_default().to_os_string();
+            name.push(format!(".{}", n));
+            if let Some(extension) = path.extension() {
+                name.push(".");
+                name.push(extension);
+            }
+            path.with_file_name(name)
+        };
+        match fs::remove_file(version(keep)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(ConfyError::WriteConfigurationFileError(e))
+            }
+            _ => {}
+        }
+        for n in (1..keep).rev() {
+            if version(n).is_file() {
+                fs::rename(version(n), version(n + 1))
+                    .map_err(ConfyError::WriteConfigurationFileError)?;
+            }
+        }
+        fs::copy(path, version(1)).map_err(ConfyError::WriteConfigurationFileError)?;
+    }
+
+    Ok(())
+}
+
+/// Save changes made to a configuration object at a specified path, with a
+/// checksum
+///
+/// This is an alternate version of [`store_path`] that additionally writes
+/// the SHA-256 checksum of the file to `<path>.sha256`, in the format of
+/// `sha256sum`, so that [`load_path_checked`] can detect a corrupted file.
+///
+/// [`store_path`]: fn.store_path.html
+/// [`load_path_checked`]: fn.load_path_checked.html
+pub fn store_path_checked<T: Serialize>(path: impl AsRef<Path>, cfg: T) -> Result<(), ConfyError> {
+    let path = path.as_ref();
+    let s = serialize(&cfg)?;
+    let checksum = format!(
+        "{}  {}\n",
+        sha256::hex_digest(s.as_bytes()),
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let options = StoreOptions::default();
+    write_config(path, s, None, &options, fs_rename)?;
+    write_config(&checksum_path(path), checksum, None, &options, fs_rename)
+}
+
+/// Load an application configuration from a specified path, verifying its
+/// checksum
+///
+/// This is an alternate version of [`load_path`] for files stored with
+/// [`store_path_checked`]: if a `<path>.sha256` checksum exists and does not
+/// match the file, e.g. because it was truncated by a full disk, this
+/// returns [`ConfyError::ChecksumMismatch`] instead of a parse error.  If
+/// there is no checksum, the file is loaded like with [`load_path`].  Every
+/// other store function removes the checksum, so files stored without one
+/// in between load fine.
+///
+/// [`load_path`]: fn.load_path.html
+/// [`store_path_checked`]: fn.store_path_checked.html
+/// [`ConfyError::ChecksumMismatch`]: enum.ConfyError.html#variant.ChecksumMismatch
+pub fn load_path_checked<T: Serialize + DeserializeOwned + Default>(
+    path: impl AsRef<Path>,
+) -> Result<T, ConfyError> {
+    let path = path.as_ref();
+    read_config(
+        path,
+        || verify_checksum(path, "").map(|()| T::default()),
+        |cfg_string| {
+            verify_checksum(path, cfg_string)?;
+            deserialize(cfg_string)
+        },
+    )
+}
+
+/// Check `cfg_string` read from `path` against the checksum written by
+/// [`store_path_checked`], if any.
+///
+/// [`store_path_checked`]: fn.store_path_checked.html
+fn verify_checksum(path: &Path, cfg_string: &str) -> Result<(), ConfyError> {
+    match fs::read_to_string(checksum_path(path)) {
+        Ok(checksum) => {
+            let expected = checksum.split_whitespace().next().unwrap_or_default();
+            if !expected.eq_ignore_ascii_case(&sha256::hex_digest(cfg_string.as_bytes())) {
+                return Err(ConfyError::ChecksumMismatch(path.to_path_buf()));
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(ConfyError::ReadConfigurationFileError(e)),
+    }
+    Ok(())
+}
+
+fn checksum_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// Remove the checksum of a previous [`store_path_checked`], which no
+/// longer matches once `path` is rewritten.
+///
+/// [`store_path_checked`]: fn.store_path_checked.html
+fn remove_stale_checksum(path: &Path) -> Result<(), ConfyError> {
+    match fs::remove_file(checksum_path(path)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            Err(ConfyError::WriteConfigurationFileError(e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Save configuration text at a specified path as-is
+///
+/// This is an alternate version of [`store_path`] for text that is already
+/// serialized, e.g. edited by the user: `contents` is checked to be valid
+/// for the configured format, and then written atomically without being
+/// re-serialized, so key order and comments are kept.  Invalid text results
+/// in the same error as loading it would, and leaves the file untouched.
+///
+/// [`store_path`]: fn.store_path.html
+pub fn store_raw_path(path: impl AsRef<Path>, contents: &str) -> Result<(), ConfyError> {
+    deserialize::<Value>(contents)?;
+    write_config(
+        path.as_ref(),
+        contents.to_string(),
+        None,
+        &StoreOptions::default(),
+        fs_rename,
+    )
+}
+
+/// Save changes made to a configuration object at a specified path, running
+/// hooks around the write
+///
+/// This is an alternate version of [`store_path`] that calls `pre_write` with
+/// the configuration right before it is serialized, e.g. to bump a
+/// modification timestamp, and `post_write` with the path once the file is
+/// in place, e.g. to trigger a sync.  `post_write` is not called if storing
+/// fails.  For more information on errors and behavior, see [`store`]'s
+/// documentation.
+///
+/// [`store`]: fn.store.html
+/// [`store_path`]: fn.store_path.html
+pub fn store_path_with_hooks<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: &mut T,
+    pre_write: impl FnOnce(&mut T),
+    post_write: impl FnOnce(&Path),
+) -> Result<(), ConfyError> {
+    let path = path.as_ref();
+    pre_write(cfg);
+    do_store(path, &*cfg, None, &StoreOptions::default(), fs_rename)?;
+    post_write(path);
+    Ok(())
+}
+
+/// Save changes made to a configuration object at a specified path, with
+/// comments
+///
+/// As serde does not know about doc comments, this is an alternate version
+/// of [`store_path`] that takes the comments to write explicitly.  Each entry
+/// of `comments` maps the dotted path of a key, e.g. `server.port`, to a
+/// comment that is written as `#`-prefixed lines above that key.  Keys
+/// without a comment, and comments for keys that do not exist, are skipped.
+/// Keys of tables nested inside of sequences can not be commented.
+///
+/// This is especially useful to write a documented initial configuration.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct MyConfig {
+///     port: u16,
+/// }
+///
+/// let path = confy::get_configuration_file_path("my-app-name", None)?;
+/// confy::store_path_with_comments(
+///     path,
+///     MyConfig::default(),
+///     &[("port", "The port the server listens on")],
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`store_path`]: fn.store_path.html
+pub fn store_path_with_comments<T: Serialize>(
+    path: impl AsRef<Path>,
+    cfg: T,
+    comments: &[(&str, &str)],
+) -> Result<(), ConfyError> {
+    let s = comments::inject(&serialize(&cfg)?, comments);
+    write_config(path.as_ref(), s, None, &StoreOptions::default(), fs_rename)
+}
+
+/// Preview the text [`store`] would write for a configuration object
+///
+/// Only the serialization step is run; the filesystem is not touched.
+/// This is useful to show a diff against the current configuration file
+/// before saving.
+///
+/// [`store`]: fn.store.html
+pub fn store_preview<T: Serialize>(cfg: T) -> Result<String, ConfyError> {
+    serialize(&cfg)
+}
+
+/// Check that a configuration object can be stored
+///
+/// The serialization step of [`store`] is run and its output discarded,
+/// without touching the filesystem, e.g. to disable a "Save" button while
+/// the configuration cannot be stored.  The error is the one [`store`]
+/// would return.
+///
+/// [`store`]: fn.store.html
+pub fn validate_serializable<T: Serialize>(cfg: &T) -> Result<(), ConfyError> {
+    serialize(cfg).map(drop)
+}
+
+/// Serialize a configuration object into raw bytes
+///
+/// This runs the same serialization step as [`store`], but hands back
+/// the bytes instead of writing them to disk.  This is useful to embed
+/// a configuration snapshot into another protocol.
+///
+/// [`store`]: fn.store.html
+pub fn store_to_bytes<T: Serialize>(cfg: T) -> Result<Vec<u8>, ConfyError> {
+    serialize(&cfg).map(String::into_bytes)
+}
+
+/// Load a configuration object from raw bytes
+///
+/// This is the counterpart of [`store_to_bytes`].  Bytes that are not
+/// valid for the configured format are reported like a bad configuration
+/// file would be by [`load_path`].
+///
+/// [`store_to_bytes`]: fn.store_to_bytes.html
+/// [`load_path`]: fn.load_path.html
+pub fn load_from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ConfyError> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => deserialize(s),
+        #[cfg(feature = "toml_conf")]
+        Err(e) => Err(ConfyError::BadTomlData(serde::de::Error::custom(e))),
+        #[cfg(feature = "yaml_conf")]
+        Err(e) => Err(ConfyError::BadYamlData(serde::de::Error::custom(e))),
+    }
+}
+
+/// Serialize a configuration object into the configured format
+///
+/// This is the serialization step of [`store`] on its own, for storing
+/// configurations somewhere confy cannot write to, e.g. the local storage of
+/// a browser.  Together with [`get_configuration_file_path`] and
+/// [`deserialize_config`], confy's path and format logic can be reused
+/// without its file handling.
+///
+/// [`store`]: fn.store.html
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+/// [`deserialize_config`]: fn.deserialize_config.html
+pub fn serialize_config<T: Serialize>(cfg: &T) -> Result<String, ConfyError> {
+    serialize(cfg)
+}
+
+/// Deserialize a configuration object from the configured format
+///
+/// This is the counterpart of [`serialize_config`], and the deserialization
+/// step of [`load`] on its own.
+///
+/// [`serialize_config`]: fn.serialize_config.html
+/// [`load`]: fn.load.html
+pub fn deserialize_config<T: DeserializeOwned>(s: &str) -> Result<T, ConfyError> {
+    deserialize(s)
+}
+
+/// Load a configuration object from a reader
+///
+/// The reader is read to the end, and its content deserialized like a
+/// configuration file would be by [`load_path`].  No path is involved, so
+/// nothing is created if the content is missing.
+///
+/// [`load_path`]: fn.load_path.html
+pub fn load_from_reader<T: DeserializeOwned>(mut reader: impl io::Read) -> Result<T, ConfyError> {
+    let mut s = String::new();
+    reader
+        .read_to_string(&mut s)
+        .map_err(ConfyError::ReadConfigurationFileError)?;
+    parse_config_string(s, deserialize)
+}
+
+/// Load a configuration object from a [`Source`]
+///
+/// The text read from `source` is deserialized like a configuration file
+/// would be by [`load_path`].  Errors of the source are passed through.
+///
+/// [`Source`]: trait.Source.html
+/// [`load_path`]: fn.load_path.html
+pub fn load_from_source<T: DeserializeOwned, S: Source + ?Sized>(
+    source: &S,
+) -> Result<T, ConfyError> {
+    parse_config_string(source.read()?, deserialize)
+}
+
+/// Save a configuration object to a writer
+///
+/// This runs the same serialization step as [`store`], but writes to the
+/// given writer directly, without any of the atomicity guarantees of
+/// writing a file.
+///
+/// [`store`]: fn.store.html
+pub fn store_to_writer<T: Serialize>(mut writer: impl Write, cfg: T) -> Result<(), ConfyError> {
+    writer
+        .write_all(serialize(&cfg)?.as_bytes())
+        .and_then(|()| writer.flush())
+        .map_err(ConfyError::WriteConfigurationFileError)
+}
+
+/// Load a configuration object from the standard input
+///
+/// This is a version of [`load_from_reader`] for piping a configuration
+/// into a program, e.g. with `--config -`.  If the standard input is empty
+/// or only contains whitespace, `T::default()` is returned.
+///
+/// [`load_from_reader`]: fn.load_from_reader.html
+pub fn load_from_stdin<T: DeserializeOwned + Default>() -> Result<T, ConfyError> {
+    load_from_stdin_or_default(io::stdin().lock())
+}
+
+fn load_from_stdin_or_default<T: DeserializeOwned + Default>(
+    mut reader: impl io::Read,
+) -> Result<T, ConfyError> {
+    let mut s = String::new();
+    reader
+        .read_to_string(&mut s)
+        .map_err(ConfyError::ReadConfigurationFileError)?;
+    if s.trim().is_empty() {
+        Ok(T::default())
+    } else {
+        parse_config_string(s, deserialize)
+    }
+}
+
+/// Save a configuration object to the standard output
+///
+/// This is a version of [`store_to_writer`] for dumping a configuration,
+/// e.g. with `--dump-config`.
+///
+/// [`store_to_writer`]: fn.store_to_writer.html
+pub fn store_to_stdout<T: Serialize>(cfg: T) -> Result<(), ConfyError> {
+    store_to_writer(io::stdout().lock(), cfg)
+}
+
+fn serialize<T: Serialize>(cfg: &T) -> Result<String, ConfyError> {
+    serialize_with(cfg, true)
+}
+
+#[cfg_attr(not(feature = "toml_conf"), allow(unused_variables))]
+fn serialize_with_options<T: Serialize>(
+    cfg: &T,
+    options: &StoreOptions,
+) -> Result<String, ConfyError> {
+    #[cfg(feature = "toml_conf")]
+    if options.sort_keys {
+        // The tables of `toml::Value` are ordered by key.
+        let value = toml::Value::try_from(cfg).map_err(ConfyError::SerializeTomlError)?;
+        return serialize_with(&value, options.pretty);
+    }
+    serialize_with(cfg, options.pretty)
+}
+
+#[cfg_attr(not(feature = "toml_conf"), allow(unused_variables))]
+fn serialize_with<T: Serialize>(cfg: &T, pretty: bool) -> Result<String, ConfyError> {
+    #[cfg(feature = "toml_conf")]
+    let result = TomlCodec { pretty }.to_string(cfg);
+    #[cfg(feature = "yaml_conf")]
+    let result = YamlCodec.to_string(cfg);
+
+    result.map(with_trailing_newline)
+}
+
+/// Make `s` end with exactly one newline, whatever the serializer does, as
+/// editors and git hooks expect.
+fn with_trailing_newline(mut s: String) -> String {
+    // Unlike after a YAML `|+` block, blank lines at the end of TOML carry
+    // no meaning.
+    #[cfg(feature = "toml_conf")]
+    s.truncate(s.trim_end_matches('\n').len());
+    if !s.ends_with('\n') {
+        s.push('\n');
+    }
+    s
+}
+
+fn deserialize<T: DeserializeOwned>(s: &str) -> Result<T, ConfyError> {
+    #[cfg(feature = "toml_conf")]
+    let result = TomlCodec::default().from_str(s);
+    #[cfg(feature = "yaml_conf")]
+    let result = YamlCodec.from_str(s);
+
+    result.map_err(|e| match &e {
+        #[cfg(feature = "toml_conf")]
+        ConfyError::BadTomlData(inner) => missing_field(&inner.to_string()).unwrap_or(e),
+        #[cfg(feature = "yaml_conf")]
+        ConfyError::BadYamlData(inner) => missing_field(&inner.to_string()).unwrap_or(e),
+        _ => e,
+    })
+}
+
+/// Recognize serde's error for a missing field, e.g. after a new field was
+/// added to the configuration.  Neither TOML nor YAML errors expose their
+/// kind, so this goes by the message.  The field is named by its dotted
+/// path, which TOML gives after the field and YAML before it.
+fn missing_field(message: &str) -> Option<ConfyError> {
+    let (before, after) = message.split_once("missing field `")?;
+    let field = after.split('`').next()?;
+    let table = after
+        .split_once("for key `")
+        .and_then(|(_, key)| key.split('`').next())
+        .or_else(|| before.strip_suffix(": "));
+    Some(ConfyError::MissingField(match table {
+        Some(table) if !table.is_empty() => format!("{}.{}", table, field),
+        _ => field.to_string(),
+    }))
+}
+
+type StoreGuard = Box<dyn Fn(&Path, &str) -> Result<(), String> + Send + Sync>;
+
+static STORE_GUARD: RwLock<Option<StoreGuard>> = RwLock::new(None);
+
+/// Install a global guard consulted before any configuration is written
+///
+/// The guard is called with the target path and the serialized content
+/// right before the file is written, by every store function of this
+/// crate.  [`import_bundle`] calls it for every file of the archive before
+/// replacing the directory, and [`migrate_location`] for the moved file.
+/// Returning an `Err` aborts the write, leaving the file untouched, and the
+/// message is returned as [`ConfyError::ValidationFailed`].
+///
+/// Installing a guard replaces the previous one.
+///
+/// [`import_bundle`]: fn.import_bundle.html
+/// [`migrate_location`]: fn.migrate_location.html
+/// [`ConfyError::ValidationFailed`]: enum.ConfyError.html#variant.ValidationFailed
+pub fn set_store_guard(guard: impl Fn(&Path, &str) -> Result<(), String> + Send + Sync + 'static) {
+    *STORE_GUARD.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(guard));
+}
+
+/// Remove the guard installed by [`set_store_guard`]
+///
+/// [`set_store_guard`]: fn.set_store_guard.html
+pub fn clear_store_guard() {
+    *STORE_GUARD.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn check_store_guard(path: &Path, content: &str) -> Result<(), ConfyError> {
+    match &*STORE_GUARD.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(guard) => guard(path, content).map_err(ConfyError::ValidationFailed),
+        None => Ok(()),
+    }
+}
+
+fn do_store<T: Serialize>(
+    path: &Path,
+    cfg: T,
+    perms: Option<Permissions>,
+    options: &StoreOptions,
+    rename: impl FnMut(&Path, &Path) -> io::Result<()>,
+) -> Result<(), ConfyError> {
+    let s = serialize_with_options(&cfg, options)?;
+    write_config(path, s, perms, options, rename)
+}
+
+fn write_config(
+    path: &Path,
+    s: String,
+    perms: Option<Permissions>,
+    options: &StoreOptions,
+    rename: impl FnMut(&Path, &Path) -> io::Result<()>,
+) -> Result<(), ConfyError> {
+    write_config_rotating(path, s, perms, options, rename, 0)
+}
+
+/// Like `write_config`, but rotate `keep` previous versions of `path` once
+/// the new content is ready to replace it, see [`store_path_rotating`].
+///
+/// [`store_path_rotating`]: fn.store_path_rotating.html
+fn write_config_rotating(
+    path: &Path,
+    s: String,
+    perms: Option<Permissions>,
+    options: &StoreOptions,
+    mut rename: impl FnMut(&Path, &Path) -> io::Result<()>,
+    keep: usize,
+) -> Result<(), ConfyError> {
+    let resolved;
+    let path = if options.follow_symlinks {
+        resolved = resolve_symlinks(path)?;
+        &resolved
+    } else {
+        path
+    };
+    if !options.atomic {
+        let s = prepare_write(path, s, options)?;
+        rotate_versions(path, keep)?;
+        write_file(path, &s, perms, options)?;
+        return remove_stale_checksum(path);
+    }
+    let path_tmp = write_temp(path, s, perms, options)?;
+    rotate_versions(path, keep)?;
+
+    let mut retries = options.rename_retries;
+    let mut delay = options.retry_delay;
+    loop {
+        match rename(&path_tmp, path) {
+            Ok(()) => {
+                // The temporary file is gone, so there is nothing to remove.
+                let _ = path_tmp.keep();
+                break;
+            }
+            Err(e)
+                if retries > 0 && (e.kind() == io::ErrorKind::PermissionDenied || is_busy(&e)) =>
+            {
+                retries -= 1;
+                std::thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+            }
+            Err(e) if is_busy(&e) => return Err(ConfyError::ConfigFileBusy(path.to_path_buf())),
+            Err(e) => return Err(ConfyError::WriteConfigurationFileError(e)),
+        }
+    }
+    remove_stale_checksum(path)?;
+    if options.durable {
+        if let Some(config_dir) = path.parent() {
+            sync_dir(config_dir).map_err(ConfyError::WriteConfigurationFileError)?;
+        }
+    }
+    Ok(())
+}
+
+/// Follow `path` through any symbolic links to the file they point to,
+/// which need not exist.
+fn resolve_symlinks(path: &Path) -> Result<PathBuf, ConfyError> {
+    let mut path = path.to_path_buf();
+    // The limit Linux uses to detect symbolic link loops.
+    for _ in 0..40 {
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target =
+                    fs::read_link(&path).map_err(ConfyError::WriteConfigurationFileError)?;
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(path),
+        }
+    }
+    Err(ConfyError::BadConfigDirectory(format!(
+        "too many levels of symbolic links at {:?}",
+        path
+    )))
+}
+
+/// Write `s` to a new temporary file next to `path`, returning its path.
+///
+/// The temporary file is removed when the returned path is dropped, unless
+/// it is kept after moving it into place.
+fn write_temp(
+    path: &Path,
+    s: String,
+    perms: Option<Permissions>,
+    options: &StoreOptions,
+) -> Result<tempfile::TempPath, ConfyError> {
+    let s = prepare_write(path, s, options)?;
+
+    let config_dir = path.parent().unwrap_or(path);
+    let prefix = format!(
+        "{}.{}_",
+        path.file_stem().unwrap_or_default().to_string_lossy(),
+        std::process::id()
+    );
+    let mut builder = tempfile::Builder::new();
+    builder
+        .prefix(&prefix)
+        .suffix(options.temp_suffix.as_deref().unwrap_or(TEMP_SUFFIX));
+    // Create the file like `File::create` would, instead of only readable by
+    // the owner.
+    #[cfg(unix)]
+    builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o666));
+    let path_tmp = builder
+        .tempfile_in(config_dir)
+        .map_err(|e| write_error(e, config_dir, ConfyError::OpenConfigurationFileError))?
+        .into_temp_path();
+
+    write_file(&path_tmp, &s, perms, options)?;
+    Ok(path_tmp)
+}
+
+/// Check that `path` can be written, create its directory, and return the
+/// final content to write.
+fn prepare_write(path: &Path, s: String, options: &StoreOptions) -> Result<String, ConfyError> {
+    let config_dir = path
+        .parent()
+        .ok_or_else(|| ConfyError::BadConfigDirectory(format!("{:?} is a root or prefix", path)))?;
+    if path.is_dir() {
+        return Err(ConfyError::PathIsDirectory(path.to_path_buf()));
+    }
+    if options.create_missing_dirs {
+        fs::create_dir_all(config_dir)
+            .map_err(|e| write_error(e, config_dir, ConfyError::DirectoryCreationFailed))?;
+    } else if !config_dir.as_os_str().is_empty() && !config_dir.is_dir() {
+        return Err(ConfyError::BadConfigDirectory(format!(
+            "{} does not exist",
+            config_dir.display()
+        )));
+    }
+    if options.cleanup_temp_files {
+        // Leftovers are harmless, so failing to remove them must not fail the store.
+        let _ = cleanup_temp_files_with_suffix(
+            path,
+            options.temp_suffix.as_deref().unwrap_or(TEMP_SUFFIX),
+        );
+    }
+
+    let s = options.text.apply(s);
+    check_store_guard(path, &s)?;
+    Ok(s)
+}
+
+/// Whether `e` means that another process holds the file open exclusively.
+/// Only Windows reports this, as `ERROR_SHARING_VIOLATION` or
+/// `ERROR_LOCK_VIOLATION`, which are otherwise indistinguishable from
+/// missing permissions.
+#[cfg(windows)]
+fn is_busy(e: &io::Error) -> bool {
+    matches!(e.raw_os_error(), Some(32) | Some(33))
+}
+
+#[cfg(not(windows))]
+fn is_busy(_e: &io::Error) -> bool {
+    false
+}
+
+/// Report failing to write to `path` because another process has it open,
+/// or because of a read-only file system or missing permissions, as
+/// [`ConfyError::ConfigFileBusy`] and [`ConfyError::ReadOnlyConfigLocation`]
+/// respectively, so that applications can tell them apart, and e.g. keep
+/// the configuration in memory.
+///
+/// [`ConfyError::ConfigFileBusy`]: enum.ConfyError.html#variant.ConfigFileBusy
+/// [`ConfyError::ReadOnlyConfigLocation`]: enum.ConfyError.html#variant.ReadOnlyConfigLocation
+fn write_error(e: io::Error, path: &Path, other: fn(io::Error) -> ConfyError) -> ConfyError {
+    if is_busy(&e) {
+        return ConfyError::ConfigFileBusy(path.to_path_buf());
+    }
+    match e.kind() {
+        io::ErrorKind::ReadOnlyFilesystem | io::ErrorKind::PermissionDenied => {
+            ConfyError::ReadOnlyConfigLocation(path.to_path_buf())
+        }
+        _ => other(e),
+    }
+}
+
+fn write_file(
+    path: &Path,
+    s: &str,
+    perms: Option<Permissions>,
+    options: &StoreOptions,
+) -> Result<(), ConfyError> {
+    let mut f = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(|e| write_error(e, path, ConfyError::OpenConfigurationFileError))?;
+
+    if let Some(p) = perms {
+        f.set_permissions(p)
+            .map_err(ConfyError::SetPermissionsFileError)?;
+    }
+
+    match options.write_buffer_size {
+        Some(size) => {
+            let size = size.max(1);
+            let mut writer = io::BufWriter::with_capacity(size, &mut f);
+            for chunk in s.as_bytes().chunks(size) {
+                writer
+                    .write_all(chunk)
+                    .map_err(ConfyError::WriteConfigurationFileError)?;
+            }
+            writer
+                .flush()
+                .map_err(ConfyError::WriteConfigurationFileError)?;
+        }
+        None => f
+            .write_all(s.as_bytes())
+            .map_err(ConfyError::WriteConfigurationFileError)?,
+    }
+    f.flush().map_err(ConfyError::WriteConfigurationFileError)?;
+    if options.durable {
+        f.sync_all()
+            .map_err(ConfyError::WriteConfigurationFileError)?;
+    }
+    Ok(())
+}
+
+/// Persist the entries of a directory, e.g. after a rename into it.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    match File::open(dir).and_then(|d| d.sync_all()) {
+        // Some file systems do not support syncing directories.
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::InvalidInput | io::ErrorKind::Unsupported
+            ) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Directories cannot be opened, and thus synced, on this platform.
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// The default suffix of temporary files, see [`StoreOptions::temp_suffix`].
+///
+/// [`StoreOptions::temp_suffix`]: struct.StoreOptions.html#structfield.temp_suffix
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// Temporary files younger than this might still be in use by a store.
+const TEMP_FILE_MIN_AGE: Duration = Duration::from_secs(60);
+
+/// Remove temporary files left behind by interrupted stores
+///
+/// Configurations are written to a temporary file next to the configuration
+/// file first, which is then renamed over it.  If the process is killed in
+/// between, the temporary file is left behind.  This removes such leftovers
+/// of the given configuration, and returns how many were removed.
+///
+/// To not interfere with concurrent stores, temporary files created by the
+/// current process, or modified within the last minute, are kept.  Only
+/// temporary files with the default suffix are found; use
+/// [`cleanup_temp_files_path_with_options`] for a custom
+/// [`StoreOptions::temp_suffix`].
+///
+/// [`cleanup_temp_files_path_with_options`]: fn.cleanup_temp_files_path_with_options.html
+/// [`StoreOptions::temp_suffix`]: struct.StoreOptions.html#structfield.temp_suffix
+pub fn cleanup_temp_files<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<usize, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(cleanup_temp_files_path)
+}
+
+/// Remove temporary files left behind by interrupted stores at a specified
+/// path
+///
+/// This is an alternate version of [`cleanup_temp_files`] that allows the
+/// specification of an arbitrary path instead of a system one.
+///
+/// [`cleanup_temp_files`]: fn.cleanup_temp_files.html
+pub fn cleanup_temp_files_path(path: impl AsRef<Path>) -> Result<usize, ConfyError> {
+    cleanup_temp_files_with_suffix(path.as_ref(), TEMP_SUFFIX)
+}
+
+/// Remove temporary files left behind by interrupted stores at a specified
+/// path, with additional options
+///
+/// This is an alternate version of [`cleanup_temp_files_path`] for
+/// configurations stored with a custom [`StoreOptions::temp_suffix`], which
+/// is used to find the temporary files.  Other options are ignored.
+///
+/// [`cleanup_temp_files_path`]: fn.cleanup_temp_files_path.html
+/// [`StoreOptions::temp_suffix`]: struct.StoreOptions.html#structfield.temp_suffix
+pub fn cleanup_temp_files_path_with_options(
+    path: impl AsRef<Path>,
+    options: &StoreOptions,
+) -> Result<usize, ConfyError> {
+    cleanup_temp_files_with_suffix(
+        path.as_ref(),
+        options.temp_suffix.as_deref().unwrap_or(TEMP_SUFFIX),
+    )
+}
+
+fn cleanup_temp_files_with_suffix(path: &Path, suffix: &str) -> Result<usize, ConfyError> {
+    let (dir, stem) = match (path.parent(), path.file_stem().and_then(|s| s.to_str())) {
+        (Some(dir), Some(stem)) => (dir, stem),
+        _ => return Ok(0),
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(ConfyError::ReadConfigurationFileError(e)),
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|n| temp_file_pid(n, stem, suffix))
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+        if pid == std::process::id() {
+            continue;
+        }
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if !matches!(age, Some(age) if age >= TEMP_FILE_MIN_AGE) {
+            continue;
+        }
+        if fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// The process id of a temporary file name, `<stem>.<pid>_<random>.tmp`.
+fn temp_file_pid(name: &str, stem: &str, suffix: &str) -> Option<u32> {
+    let rest = name.strip_prefix(stem)?.strip_prefix('.')?;
+    let (pid, rest) = rest.split_once('_')?;
+    let pid = pid.parse().ok()?;
+    match rest.strip_suffix(suffix) {
+        Some(random) if !random.is_empty() && random.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            Some(pid)
+        }
+        Some(_) => None,
+        // `ThreadId(<n>)_<nanos>`, as named by earlier versions.
+        None => {
+            let (thread, nanos) = rest.split_once('_')?;
+            thread
+                .strip_prefix("ThreadId(")?
+                .strip_suffix(')')?
+                .parse::<u64>()
+                .ok()?;
+            nanos.parse::<u128>().ok()?;
+            Some(pid)
+        }
+    }
+}
+
+fn fs_rename(from: &Path, to: &Path) -> io::Result<()> {
+    fs::rename(from, to)
+}
+
+/// Move an application configuration to a new name
+///
+/// This is meant for renamed applications, on their first launch: if the
+/// configuration of `old_app_name` exists and the one of `new_app_name`
+/// does not, the file is moved to the new location.  Returns whether it was
+/// moved.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # fn main() -> Result<(), ConfyError> {
+/// if confy::migrate_location("old-app-name", None, "new-app-name", None)? {
+///     println!("Moved your settings from old-app-name");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn migrate_location<'a, 'b>(
+    old_app_name: &str,
+    old_config_name: impl Into<Option<&'a str>>,
+    new_app_name: &str,
+    new_config_name: impl Into<Option<&'b str>>,
+) -> Result<bool, ConfyError> {
+    let old = get_configuration_file_path(old_app_name, old_config_name)?;
+    let new = get_configuration_file_path(new_app_name, new_config_name)?;
+    migrate_location_path(old, new)
+}
+
+/// Move a configuration file to a new path
+///
+/// This is an alternate version of [`migrate_location`] that allows the
+/// specification of arbitrary paths instead of system ones.  The file is
+/// renamed if possible, and otherwise, e.g. across file systems, copied and
+/// the original removed.
+///
+/// [`migrate_location`]: fn.migrate_location.html
+pub fn migrate_location_path(
+    old: impl AsRef<Path>,
+    new: impl AsRef<Path>,
+) -> Result<bool, ConfyError> {
+    let (old, new) = (old.as_ref(), new.as_ref());
+    if !old.is_file() || new.exists() {
+        return Ok(false);
+    }
+    let content = fs::read(old).map_err(ConfyError::ReadConfigurationFileError)?;
+    check_store_guard(new, &String::from_utf8_lossy(&content))?;
+    if let Some(dir) = new.parent() {
+        fs::create_dir_all(dir).map_err(ConfyError::DirectoryCreationFailed)?;
+    }
+    if fs::rename(old, new).is_err() {
+        fs::copy(old, new).map_err(ConfyError::WriteConfigurationFileError)?;
+        fs::remove_file(old).map_err(ConfyError::WriteConfigurationFileError)?;
+    }
+    remove_stale_checksum(new)?;
+    Ok(true)
+}
+
+/// Export the whole configuration directory of an application
+///
+/// All files in the configuration directory of `app_name` are written into
+/// a single tar archive at `dst`, e.g. for an "export settings" feature.
+/// Use [`import_bundle`] to restore it.
+///
+/// [`import_bundle`]: fn.import_bundle.html
+pub fn export_bundle(app_name: &str, dst: impl AsRef<Path>) -> Result<(), ConfyError> {
+    export_bundle_path(configuration_directory(app_name)?, dst)
+}
+
+/// Export a configuration directory at a specified path
+///
+/// This is an alternate version of [`export_bundle`] that allows the
+/// specification of an arbitrary directory instead of a system one.
+///
+/// [`export_bundle`]: fn.export_bundle.html
+pub fn export_bundle_path(dir: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<(), ConfyError> {
+    bundle::export(dir.as_ref(), dst.as_ref())
+}
+
+/// Import the whole configuration directory of an application
+///
+/// The configuration directory of `app_name` is replaced with the content
+/// of the archive at `src`, as written by [`export_bundle`].  The archive
+/// is validated before the existing directory is touched, and
+/// [`ConfyError::BadBundle`] is returned if it is malformed or contains
+/// paths escaping the configuration directory.
+///
+/// [`export_bundle`]: fn.export_bundle.html
+/// [`ConfyError::BadBundle`]: enum.ConfyError.html#variant.BadBundle
+pub fn import_bundle(app_name: &str, src: impl AsRef<Path>) -> Result<(), ConfyError> {
+    import_bundle_path(configuration_directory(app_name)?, src)
+}
+
+/// Import a configuration directory at a specified path
+///
+/// This is an alternate version of [`import_bundle`] that allows the
+/// specification of an arbitrary directory instead of a system one.
+///
+/// [`import_bundle`]: fn.import_bundle.html
+pub fn import_bundle_path(dir: impl AsRef<Path>, src: impl AsRef<Path>) -> Result<(), ConfyError> {
+    bundle::import(dir.as_ref(), src.as_ref())
+}
+
+/// Get the configuration file path used by [`load`] and [`store`]
+///
+/// This is useful if you want to show where the configuration file is to your user.
+///
+/// [`load`]: fn.load.html
+/// [`store`]: fn.store.html
+pub fn get_configuration_file_path<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<PathBuf, ConfyError> {
+    get_configuration_file_path_with_extension(app_name, config_name, EXTENSION)
+}
+
+/// The paths confy uses for a configuration, see [`resolved_paths`]
+///
+/// [`resolved_paths`]: fn.resolved_paths.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPaths {
+    /// The configuration file, as returned by [`get_configuration_file_path`].
+    ///
+    /// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+    pub config_file: PathBuf,
+
+    /// The configuration directory, as returned by [`get_config_dir`].
+    ///
+    /// [`get_config_dir`]: fn.get_config_dir.html
+    pub config_dir: PathBuf,
+
+    /// The configuration file next to the executable used by
+    /// [`load_portable`], if the executable can be located.
+    ///
+    /// [`load_portable`]: fn.load_portable.html
+    pub portable_file: Option<PathBuf>,
+}
+
+/// Get all paths confy uses for an application configuration
+///
+/// This collects the paths of [`get_configuration_file_path`] and related
+/// functions in one place, e.g. for a diagnostic command printing where the
+/// configuration is looked for.  Nothing is created.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # fn main() -> Result<(), ConfyError> {
+/// let paths = confy::resolved_paths("my-app-name", None)?;
+/// println!("configuration file: {}", paths.config_file.display());
+/// println!("configuration directory: {}", paths.config_dir.display());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+pub fn resolved_paths<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<ResolvedPaths, ConfyError> {
+    let config_name = config_name.into();
+    Ok(ResolvedPaths {
+        config_file: get_configuration_file_path(app_name, config_name)?,
+        config_dir: get_config_dir(app_name)?,
+        portable_file: get_portable_config_path(config_name).ok(),
+    })
+}
+
+/// Get the path of a configuration file next to the executable
+///
+/// This is an alternate version of [`get_configuration_file_path`] for
+/// portable applications: the path is `<config_name>.<ext>` in the directory
+/// of the running executable.  If the executable cannot be located, e.g.
+/// because it was deleted, [`ConfyError::BadConfigDirectory`] is returned.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+/// [`ConfyError::BadConfigDirectory`]: enum.ConfyError.html#variant.BadConfigDirectory
+pub fn get_portable_config_path<'a>(
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<PathBuf, ConfyError> {
+    let exe = std::env::current_exe().map_err(|e| {
+        ConfyError::BadConfigDirectory(format!("could not locate the executable: {}", e))
+    })?;
+    let dir = exe.parent().ok_or_else(|| {
+        ConfyError::BadConfigDirectory(format!("{:?} has no parent directory", exe))
+    })?;
+    let config_name = config_name.into().unwrap_or(DEFAULT_CONFIG_NAME);
+    Ok(dir.join(format!("{}.{}", config_name, EXTENSION)))
+}
+
+/// Get the configuration directory of an application
+///
+/// This is the directory [`get_configuration_file_path`] places the
+/// configuration files in, e.g. to put a log or cache file next to them.
+/// The directory is not created.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+pub fn get_config_dir(app_name: &str) -> Result<PathBuf, ConfyError> {
+    configuration_directory(app_name)
+}
+
+/// Get the configuration file path for display to the user
+///
+/// This is an alternate version of [`get_configuration_file_path`] for
+/// help texts and similar.  If the configuration directory cannot be
+/// determined, a placeholder path like
+/// `<platform config dir>/my-app/default-config.toml` is returned instead of
+/// an error.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+pub fn config_path_display<'a>(app_name: &str, config_name: impl Into<Option<&'a str>>) -> String {
+    let config_name = config_name.into();
+    match get_configuration_file_path(app_name, config_name) {
+        Ok(path) => path.display().to_string(),
+        Err(_) => format!(
+            "<platform config dir>/{}/{}.{}",
+            app_name,
+            config_name.unwrap_or(DEFAULT_CONFIG_NAME),
+            EXTENSION
+        ),
+    }
+}
+
+/// The size and modification time of a configuration file, see
+/// [`config_metadata`]
+///
+/// [`config_metadata`]: fn.config_metadata.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigMeta {
+    /// When the file was last modified.
+    pub modified: SystemTime,
+
+    /// The size of the file in bytes.
+    pub size: u64,
+}
+
+/// Get the size and modification time of an application configuration
+///
+/// The file is neither read nor created, e.g. to show when the
+/// configuration was last saved.  `None` is returned if it does not exist.
+pub fn config_metadata<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<Option<ConfigMeta>, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(config_metadata_path)
+}
+
+/// Get the size and modification time of a configuration file at a
+/// specified path
+///
+/// This is an alternate version of [`config_metadata`] that allows the
+/// specification of an arbitrary path instead of a system one.
+///
+/// [`config_metadata`]: fn.config_metadata.html
+pub fn config_metadata_path(path: impl AsRef<Path>) -> Result<Option<ConfigMeta>, ConfyError> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(ConfyError::GeneralLoadError(e)),
+    };
+    Ok(Some(ConfigMeta {
+        modified: metadata.modified().map_err(ConfyError::GeneralLoadError)?,
+        size: metadata.len(),
+    }))
+}
+
+/// Check whether an application configuration could be stored
+///
+/// This checks the permissions of the configuration file, if it exists,
+/// and of its directory, or the closest existing parent directory, without
+/// writing anything, e.g. to show a configuration on read-only media as
+/// locked.
+///
+/// On Unix, this asks the operating system with `access(2)`, which takes
+/// ownership, the permission bits and read-only mounts into account.  On
+/// Windows, only the read-only attribute of the file is checked, not access
+/// control lists, so a store may still fail with e.g.
+/// [`ConfyError::ReadOnlyConfigLocation`].
+///
+/// [`ConfyError::ReadOnlyConfigLocation`]: enum.ConfyError.html#variant.ReadOnlyConfigLocation
+pub fn is_writable<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<bool, ConfyError> {
+    get_configuration_file_path(app_name, config_name).and_then(is_writable_path)
+}
+
+/// Check whether a configuration could be stored at a specified path
+///
+/// This is an alternate version of [`is_writable`] that allows the
+/// specification of an arbitrary path instead of a system one.
+///
+/// [`is_writable`]: fn.is_writable.html
+pub fn is_writable_path(path: impl AsRef<Path>) -> Result<bool, ConfyError> {
+    let path = path.as_ref();
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => {
+            return Err(ConfyError::PathIsDirectory(path.to_path_buf()))
+        }
+        Ok(metadata) if !can_write(path, &metadata) => return Ok(false),
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(ConfyError::GeneralLoadError(e)),
+    }
+
+    // Stores write a temporary file next to the configuration, and create
+    // missing directories, so the closest existing one must be writable.
+    for dir in path.ancestors().skip(1) {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        match fs::metadata(dir) {
+            Ok(metadata) => return Ok(can_write(dir, &metadata)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(ConfyError::GeneralLoadError(e)),
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the current user may write to `path`, which exists.
+#[cfg(unix)]
+fn can_write(path: &Path, _metadata: &fs::Metadata) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    match std::ffi::CString::new(path.as_os_str().as_bytes()) {
+        // SAFETY: `path` is a valid NUL-terminated string for the duration
+        // of the call.
+        Ok(path) => unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 },
+        Err(_) => false,
+    }
+}
+
+/// Whether the current user may write to `path`, which exists.  The
+/// read-only attribute of directories is ignored on Windows.
+#[cfg(not(unix))]
+fn can_write(_path: &Path, metadata: &fs::Metadata) -> bool {
+    metadata.is_dir() || !metadata.permissions().readonly()
+}
+
+/// Get the configuration file path, using a custom file extension
+///
+/// This is an alternate version of [`get_configuration_file_path`] for
+/// configuration files whose extension does not match the format, e.g.
+/// `config.cfg` holding TOML data.  The extension is given without the
+/// leading dot.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+pub fn get_configuration_file_path_with_extension<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    extension: &str,
+) -> Result<PathBuf, ConfyError> {
+    configuration_file_path(app_name, config_name.into(), DEFAULT_CONFIG_NAME, extension)
+}
+
+/// Get the path of a configuration file in a subdirectory of the
+/// configuration directory
+///
+/// This is an alternate version of [`get_configuration_file_path`] for
+/// applications grouping their configurations into folders.  The segments
+/// are joined below the application's configuration directory, and the last
+/// one gets the extension, e.g. `["profiles", "work"]` gives
+/// `<config_dir>/profiles/work.toml`.  The store functions create the
+/// intermediate directories as needed.
+///
+/// A segment that is empty, absolute or contains `..` results in
+/// [`ConfyError::BadConfigDirectory`], so the path can never escape the
+/// configuration directory.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+/// [`ConfyError::BadConfigDirectory`]: enum.ConfyError.html#variant.BadConfigDirectory
+pub fn get_configuration_file_path_nested(
+    app_name: &str,
+    segments: &[&str],
+) -> Result<PathBuf, ConfyError> {
+    let (last, dirs) = segments
+        .split_last()
+        .ok_or_else(|| ConfyError::BadConfigDirectory("no configuration name given".to_string()))?;
+    for segment in segments {
+        let mut components = Path::new(segment).components();
+        if segment.is_empty() || !components.all(|c| matches!(c, Component::Normal(_))) {
+            return Err(ConfyError::BadConfigDirectory(format!(
+                "invalid path segment {:?}",
+                segment
+            )));
+        }
+    }
+
+    let mut path = configuration_directory(app_name)?;
+    path.extend(dirs);
+    path.push(format!("{}.{}", last, EXTENSION));
+    Ok(path)
+}
+
+/// Where on the machine a configuration is stored, see
+/// [`get_configuration_file_path_scoped`].
+///
+/// [`get_configuration_file_path_scoped`]: fn.get_configuration_file_path_scoped.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigScope {
+    /// The regular configuration directory.  On Windows, this is in the
+    /// roaming `AppData`, which follows the user across machines.
+    Roaming,
+
+    /// A machine-specific configuration directory.  On Windows, this is in
+    /// the local `AppData`; on other platforms it is the same as `Roaming`.
+    Local,
+}
+
+/// Get the configuration file path for a given [`ConfigScope`]
+///
+/// This is an alternate version of [`get_configuration_file_path`] for
+/// settings which should not roam across machines on Windows, like device
+/// identifiers.  On other platforms, the scope makes no difference.
+///
+/// [`ConfigScope`]: enum.ConfigScope.html
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+pub fn get_configuration_file_path_scoped<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    scope: ConfigScope,
+) -> Result<PathBuf, ConfyError> {
+    configuration_file_path_scoped(
+        app_name,
+        config_name.into(),
+        DEFAULT_CONFIG_NAME,
+        EXTENSION,
+        scope,
+    )
+}
+
+/// Get the configuration file path, with a custom directory policy
+///
+/// This is an alternate version of [`get_configuration_file_path`] that asks
+/// `strategy` for the configuration directory instead of following the
+/// platform conventions.  If it returns `None`,
+/// [`ConfyError::BadConfigDirectory`] is returned.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+/// [`ConfyError::BadConfigDirectory`]: enum.ConfyError.html#variant.BadConfigDirectory
+pub fn get_configuration_file_path_with_strategy<'a>(
+    strategy: &impl PathStrategy,
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> Result<PathBuf, ConfyError> {
+    let dir = strategy.config_dir(app_name).ok_or_else(|| {
+        ConfyError::BadConfigDirectory(format!(
+            "could not determine the configuration directory of {}",
+            app_name
+        ))
+    })?;
+    let config_name = config_name.into().unwrap_or(DEFAULT_CONFIG_NAME);
+    Ok(dir.join(format!("{}.{}", config_name, EXTENSION)))
+}
+
+/// Get the configuration file path, using a custom default configuration
+/// name
+///
+/// This is an alternate version of [`get_configuration_file_path`] that uses
+/// `default_name` instead of `"default-config"` when `config_name` is
+/// `None`.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+pub fn get_configuration_file_path_with_default<'a>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    default_name: &str,
+) -> Result<PathBuf, ConfyError> {
+    configuration_file_path(app_name, config_name.into(), default_name, EXTENSION)
+}
+
+/// Get the configuration file path below an explicit base directory
+///
+/// Unlike [`get_configuration_file_path`], the platform configuration
+/// directory (and with it e.g. `XDG_CONFIG_HOME`) is not consulted at all:
+/// the path is always `<base_dir>/<app_name>/<config_name>.<ext>`.  This
+/// gives deterministic paths, e.g. for integration tests.
+///
+/// [`get_configuration_file_path`]: fn.get_configuration_file_path.html
+pub fn get_configuration_file_path_in<'a>(
+    base_dir: impl AsRef<Path>,
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+) -> PathBuf {
+    let config_name = config_name.into().unwrap_or(DEFAULT_CONFIG_NAME);
+    base_dir
+        .as_ref()
+        .join(app_name)
+        .join(format!("{}.{}", config_name, EXTENSION))
+}
+
+fn configuration_file_path(
+    app_name: &str,
+    config_name: Option<&str>,
+    default_name: &str,
+    extension: &str,
+) -> Result<PathBuf, ConfyError> {
+    configuration_file_path_scoped(
+        app_name,
+        config_name,
+        default_name,
+        extension,
+        ConfigScope::Roaming,
+    )
+}
+
+fn configuration_file_path_scoped(
+    app_name: &str,
+    config_name: Option<&str>,
+    default_name: &str,
+    extension: &str,
+    scope: ConfigScope,
+) -> Result<PathBuf, ConfyError> {
+    let config_name = config_name.unwrap_or(default_name);
+    let config_dir = configuration_directory_scoped(app_name, scope)?;
+
+    let config_dir_str = get_configuration_directory_str(&config_dir)?;
+
+    let path = [config_dir_str, &format!("{}.{}", config_name, extension)]
+        .iter()
+        .collect();
+
+    Ok(path)
+}
+
+fn configuration_directory(app_name: &str) -> Result<PathBuf, ConfyError> {
+    configuration_directory_scoped(app_name, ConfigScope::Roaming)
+}
+
+fn configuration_directory_scoped(
+    app_name: &str,
+    scope: ConfigScope,
+) -> Result<PathBuf, ConfyError> {
+    let project = ProjectDirs::from("rs", "", app_name).ok_or_else(|| {
+        ConfyError::BadConfigDirectory(missing_home_message(|var| std::env::var_os(var)))
+    })?;
+    match scope {
+        // `data_local_dir` is `{FOLDERID_LocalAppData}\<app>\data`, next to
+        // which the roaming layout would put `config`.
+        ConfigScope::Local if cfg!(windows) => Ok(project
+            .data_local_dir()
+            .parent()
+            .unwrap_or_else(|| project.data_local_dir())
+            .join("config")),
+        _ => Ok(project.config_dir().to_path_buf()),
+    }
+}
+
+/// The environment variables the configuration directory is derived from.
+#[cfg(windows)]
+const HOME_VARS: &[&str] = &["APPDATA", "LOCALAPPDATA", "USERPROFILE"];
+#[cfg(not(windows))]
+const HOME_VARS: &[&str] = &["HOME"];
+
+/// Explain why the configuration directory could not be determined, naming
+/// the environment variables that are unset or empty.
+fn missing_home_message(var: impl Fn(&str) -> Option<std::ffi::OsString>) -> String {
+    let empty: Vec<_> = HOME_VARS
+        .iter()
+        .filter(|name| !matches!(var(name), Some(value) if !value.is_empty()))
+        .copied()
+        .collect();
+    let mut message = "could not determine home directory path".to_string();
+    if empty.is_empty() {
+        message.push_str(&format!(" (checked {})", HOME_VARS.join(", ")));
+    } else {
+        message.push_str(&format!(" ({} unset or empty)", empty.join(", ")));
+    }
+    message
+}
+
+fn get_configuration_directory_str(path: &Path) -> Result<&str, ConfyError> {
+    path.to_str()
+        .ok_or_else(|| ConfyError::BadConfigDirectory(format!("{:?} is not valid Unicode", path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serializer;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    #[derive(PartialEq, Default, Debug, Serialize, Deserialize)]
+    struct ExampleConfig {
+        name: String,
+        count: usize,
+    }
+
+    /// Run a test function with a temporary config path as fixture.
+    fn with_config_path(test_fn: fn(&Path)) {
+        let config_dir = tempfile::tempdir().expect("creating test fixture failed");
+        // config_path should roughly correspond to the result of `get_configuration_file_path("example-app", "example-config")`
+        let config_path = config_dir
+            .path()
+            .join("example-app")
+            .join("example-config")
+            .with_extension(EXTENSION);
+        test_fn(&config_path);
+        config_dir.close().expect("removing test fixture failed");
+    }
+
+    /// Whether the tests run as root, who may write regardless of the
+    /// permission bits.
+    #[cfg(unix)]
+    fn is_root() -> bool {
+        // SAFETY: `geteuid` has no preconditions.
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    #[cfg(not(unix))]
+    fn is_root() -> bool {
+        false
+    }
+
+    /// Run `f` with `guard` installed, one test at a time, as the guard is
+    /// global.
+    fn with_store_guard<R>(
+        guard: impl Fn(&Path, &str) -> Result<(), String> + Send + Sync + 'static,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_store_guard(guard);
+        let result = f();
+        clear_store_guard();
+        result
+    }
+
+    /// [`load_path`] loads [`ExampleConfig`].
+    #[test]
+    fn load_path_works() {
+        with_config_path(|path| {
+            let config: ExampleConfig = load_path(path).expect("load_path failed");
+            assert_eq!(config, ExampleConfig::default());
+        })
+    }
+
+    /// [`load_path`] names a missing field.
+    #[test]
+    fn test_load_path_missing_field() {
+        with_config_path(|path| {
+            #[derive(Serialize)]
+            struct NameOnly {
+                name: String,
+            }
+            let name_only = NameOnly {
+                name: "Test".to_string(),
+            };
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, serialize_config(&name_only).unwrap()).unwrap();
+            match load_path::<ExampleConfig>(path) {
+                Err(ConfyError::MissingField(field)) => assert_eq!(field, "count"),
+                other => panic!("unexpected result {:?}", other),
+            }
+        })
+    }
+
+    /// Missing fields of nested tables are named by their dotted path.
+    #[test]
+    fn test_missing_field_path() {
+        let field = |message| match missing_field(message) {
+            Some(ConfyError::MissingField(field)) => field,
+            other => panic!("unexpected result {:?}", other),
+        };
+        assert_eq!(
+            field("missing field `count` for key `section.inner` at line 1 column 1"),
+            "section.inner.count"
+        );
+        assert_eq!(
+            field("section.inner: missing field `count` at line 2 column 3"),
+            "section.inner.count"
+        );
+        assert_eq!(field("missing field `count` at line 1 column 1"), "count");
+        assert!(missing_field("invalid type: string, expected usize").is_none());
+    }
+
+    /// A missing field of a nested table is reported with its table.
+    #[test]
+    #[cfg(feature = "toml_conf")]
+    fn test_load_nested_missing_field() {
+        #[derive(Debug, Deserialize)]
+        struct Nested {
+            #[allow(dead_code)]
+            section: ExampleConfig,
+        }
+
+        match deserialize::<Nested>("[section]\nname = \"Test\"\n") {
+            Err(ConfyError::MissingField(field)) => assert_eq!(field, "section.count"),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    /// [`store_path`] stores [`ExampleConfig`].
+    #[test]
+    fn test_store_path() {
+        with_config_path(|path| {
+            let config: ExampleConfig = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_path(path, &config).expect("store_path failed");
+            let loaded = load_path(path).expect("load_path failed");
+            assert_eq!(config, loaded);
+        })
+    }
+
+    /// [`store_path_perms`] stores [`ExampleConfig`], with only read permission for owner (UNIX).
+    #[test]
+    #[cfg(unix)]
+    fn test_store_path_perms() {
+        with_config_path(|path| {
+            let config: ExampleConfig = ExampleConfig {
+                name: "Secret".to_string(),
+                count: 16549,
+            };
+            store_path_perms(path, &config, Permissions::from_mode(0o600))
+                .expect("store_path_perms failed");
+            let loaded = load_path(path).expect("load_path failed");
+            assert_eq!(config, loaded);
+        })
+    }
+
+    /// [`load_path_secure`] refuses a world-writable file, but loads a private one.
+    #[test]
+    #[cfg(unix)]
+    fn test_load_path_secure() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Secure".to_string(),
+                count: 22,
+            };
+            store_path_perms(path, &config, Permissions::from_mode(0o666))
+                .expect("store_path_perms failed");
+            fs::set_permissions(path.parent().unwrap(), Permissions::from_mode(0o700))
+                .expect("setting directory permissions failed");
+            let err =
+                load_path_secure::<ExampleConfig>(path).expect_err("load_path_secure should fail");
+            assert!(matches!(err, ConfyError::InsecurePermissions(p) if p == path));
+
+            fs::set_permissions(path, Permissions::from_mode(0o600))
+                .expect("setting file permissions failed");
+            let loaded: ExampleConfig = load_path_secure(path).expect("load_path_secure failed");
+            assert_eq!(config, loaded);
+        })
+    }
+
+    /// [`load_path_merging_defaults`] fills in fields missing from the file.
+    #[test]
+    fn test_load_path_merging_defaults() {
+        with_config_path(|path| {
+            #[derive(Serialize)]
+            struct Partial {
+                name: String,
+            }
+
+            #[derive(PartialEq, Debug, Serialize, Deserialize)]
+            struct Full {
+                name: String,
+                count: usize,
+            }
+
+            impl Default for Full {
+                fn default() -> Self {
+                    Full {
+                        name: "Default".to_string(),
+                        count: 8,
+                    }
+                }
+            }
+
+            store_path(
+                path,
+                Partial {
+                    name: "Partial".to_string(),
+                },
+            )
+            .expect("store_path failed");
+            assert!(load_path::<Full>(path).is_err());
+
+            let loaded: Full =
+                load_path_merging_defaults(path).expect("load_path_merging_defaults failed");
+            assert_eq!(
+                loaded,
+                Full {
+                    name: "Partial".to_string(),
+                    count: 8,
+                }
+            );
+        })
+    }
+
+    /// [`load_with_provenance`] attributes every key to the winning layer.
+    #[test]
+    fn test_load_with_provenance() {
+        let defaults = value::to_value(&ExampleConfig {
+            name: "Default".to_string(),
+            count: 1,
+        })
+        .unwrap();
+        #[derive(Serialize)]
+        struct CountOnly {
+            count: usize,
+        }
+        let file = value::to_value(&CountOnly { count: 2 }).unwrap();
+
+        let (config, provenance): (ExampleConfig, _) =
+            load_with_provenance(&[("defaults", defaults), ("file", file)])
+                .expect("load_with_provenance failed");
+
+        assert_eq!(
+            config,
+            ExampleConfig {
+                name: "Default".to_string(),
+                count: 2,
+            }
+        );
+        assert_eq!(provenance.len(), 2);
+        assert_eq!(provenance["name"], "defaults");
+        assert_eq!(provenance["count"], "file");
+    }
+
+    /// [`LoadOptions::yaml_tab_width`] accepts tab-indented YAML.
+    #[test]
+    #[cfg(feature = "yaml_conf")]
+    fn test_load_path_yaml_tabs() {
+        with_config_path(|path| {
+            #[derive(PartialEq, Default, Debug, Serialize, Deserialize)]
+            struct Nested {
+                example: ExampleConfig,
+            }
+
+            fs::create_dir_all(path.parent().unwrap()).expect("creating directory failed");
+            fs::write(path, "example:\n\tname: Tabs\n\tcount: 9\n").expect("writing failed");
+            assert!(load_path::<Nested>(path).is_err());
+
+            let options = LoadOptions {
+                yaml_tab_width: Some(2),
+                ..LoadOptions::default()
+            };
+            let loaded: Nested =
+                load_path_with_options(path, &options).expect("load_path_with_options failed");
+            assert_eq!(loaded.example.name, "Tabs");
+            assert_eq!(loaded.example.count, 9);
+        })
+    }
+
+    /// [`load_path_validated`] reports the validator's error.
+    #[test]
+    fn test_load_path_validated() {
+        with_config_path(|path| {
+            store_path(path, ExampleConfig::default()).expect("store_path failed");
+            let validate = |cfg: &ExampleConfig| {
+                if cfg.name.is_empty() {
+                    return Err("name must not be empty".to_string());
+                }
+                Ok(())
+            };
+            let err =
+                load_path_validated(path, validate).expect_err("load_path_validated should fail");
+            assert_eq!(
+                err.to_string(),
+                "Configuration validation failed: name must not be empty"
+            );
+
+            let config = ExampleConfig {
+                name: "Valid".to_string(),
+                count: 1,
+            };
+            store_path(path, &config).expect("store_path failed");
+            let loaded = load_path_validated(path, validate).expect("load_path_validated failed");
+            assert_eq!(config, loaded);
+        })
+    }
+
+    /// [`load_path_with_embedded_fallback`] parses the embedded copy when the
+    /// configuration can not be read, without writing anything.
+    #[test]
+    fn test_load_path_with_embedded_fallback() {
+        with_config_path(|path| {
+            // A file where the configuration directory should be.
+            fs::write(path.parent().unwrap(), "").expect("creating file failed");
+
+            let embedded = store_to_bytes(ExampleConfig {
+                name: "Embedded".to_string(),
+                count: 3,
+            })
+            .expect("store_to_bytes failed");
+            let embedded = String::from_utf8(embedded).unwrap();
+
+            let loaded: ExampleConfig = load_path_with_embedded_fallback(path, &embedded)
+                .expect("load_path_with_embedded_fallback failed");
+            assert_eq!(loaded.name, "Embedded");
+            assert!(!path.exists());
+        })
+    }
+
+    /// [`store_path_perms`] stores [`ExampleConfig`], as read-only.
+    #[test]
+    fn test_store_path_perms_readonly() {
+        with_config_path(|path| {
+            let config: ExampleConfig = ExampleConfig {
+                name: "Soon read-only".to_string(),
+                count: 27115,
+            };
+            store_path(path, &config).expect("store_path failed");
+
+            let metadata = fs::metadata(path).expect("reading metadata failed");
+            let mut permissions = metadata.permissions();
+            permissions.set_readonly(true);
+
+            store_path_perms(path, &config, permissions).expect("store_path_perms failed");
+
+            assert!(fs::metadata(path)
+                .expect("reading metadata failed")
+                .permissions()
+                .readonly());
+        })
+    }
+
+    /// [`load_path`] reports files with a wrong encoding as such.
+    #[test]
+    fn test_load_path_non_utf8() {
+        with_config_path(|path| {
+            fs::create_dir_all(path.parent().unwrap()).expect("creating directory failed");
+            fs::write(path, b"name = \"\xff\xfe\"\n").expect("writing config failed");
+            let err = load_path::<ExampleConfig>(path).expect_err("load_path should fail");
+            assert!(matches!(err, ConfyError::NonUtf8Config(p) if p == path));
+        })
+    }
+
+    /// [`load_path`] and [`store_path`] report a directory at the config path.
+    #[test]
+    fn test_path_is_directory() {
+        with_config_path(|path| {
+            fs::create_dir_all(path).expect("creating directory failed");
+            let err = load_path::<ExampleConfig>(path).expect_err("load_path should fail");
+            assert!(matches!(err, ConfyError::PathIsDirectory(p) if p == path));
+            let err =
+                store_path(path, ExampleConfig::default()).expect_err("store_path should fail");
+            assert!(matches!(err, ConfyError::PathIsDirectory(p) if p == path));
+        })
+    }
+
+    /// An exported configuration directory can be imported somewhere else.
+    #[test]
+    fn test_bundle_roundtrip() {
+        let fixture = tempfile::tempdir().expect("creating test fixture failed");
+        let source = fixture.path().join("source");
+        let config = ExampleConfig {
+            name: "Bundled".to_string(),
+            count: 77,
+        };
+        store_path(source.join("default-config.toml"), &config).expect("store_path failed");
+        store_path(source.join("profiles").join("work.toml"), &config).expect("store_path failed");
+        fs::create_dir_all(source.join("empty")).expect("creating directory failed");
+
+        let archive = fixture.path().join("bundle.tar");
+        export_bundle_path(&source, &archive).expect("export_bundle_path failed");
+
+        let target = fixture.path().join("target");
+        store_path(target.join("stale.toml"), &config).expect("store_path failed");
+        // Checksums of the replaced directory are not carried over.
+        store_path_checked(target.join("default-config.toml"), ExampleConfig::default())
+            .expect("store_path_checked failed");
+        import_bundle_path(&target, &archive).expect("import_bundle_path failed");
+
+        assert!(!target.join("stale.toml").exists());
+        assert!(!checksum_path(&target.join("default-config.toml")).exists());
+        assert!(target.join("empty").is_dir());
+        for file in &["default-config.toml", "profiles/work.toml"] {
+            assert_eq!(
+                fs::read(source.join(file)).unwrap(),
+                fs::read(target.join(file)).unwrap()
+            );
+        }
+        assert_eq!(fs::read_dir(fixture.path()).unwrap().count(), 3);
+    }
+
+    /// A store guard vetoing any file of a bundle leaves the directory
+    /// unchanged.
+    #[test]
+    fn test_import_bundle_guard_veto() {
+        let fixture = tempfile::tempdir().expect("creating test fixture failed");
+        let source = fixture.path().join("source");
+        let config = ExampleConfig {
+            name: "Forbidden".to_string(),
+            count: 1,
+        };
+        store_path(source.join("default-config.toml"), &config).expect("store_path failed");
+        let archive = fixture.path().join("bundle.tar");
+        export_bundle_path(&source, &archive).expect("export_bundle_path failed");
+
+        let target = fixture.path().join("target");
+        store_path(target.join("stale.toml"), ExampleConfig::default()).expect("store_path failed");
+        let guarded = target.clone();
+        let guard = move |path: &Path, content: &str| {
+            if path.starts_with(&guarded) && content.contains("Forbidden") {
+                return Err("forbidden name".to_string());
+            }
+            Ok(())
+        };
+        let result = with_store_guard(guard, || import_bundle_path(&target, &archive));
+
+        assert!(matches!(result, Err(ConfyError::ValidationFailed(_))));
+        assert!(target.join("stale.toml").exists());
+        assert!(!target.join("default-config.toml").exists());
+    }
+
+    /// [`store_path`] fails when given a root path.
+    #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    fn test_store_path_root_error() {
+        let err = store_path(PathBuf::from("/"), &ExampleConfig::default())
+            .expect_err("store_path should fail");
+        assert_eq!(
+            err.to_string(),
+            r#"Bad configuration directory: "/" is a root or prefix"#,
+        )
+    }
+
+    /// [`store_preview`] matches what [`store_path`] writes.
+    #[test]
+    fn test_store_preview() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Preview".to_string(),
+                count: 5,
+            };
+            let preview = store_preview(&config).expect("store_preview failed");
+            assert!(!path.exists());
+            store_path(path, &config).expect("store_path failed");
+            assert_eq!(
+                preview,
+                fs::read_to_string(path).expect("reading config failed")
+            );
+        })
+    }
+
+    /// [`store_to_bytes`] output can be read back with [`load_from_bytes`].
+    #[test]
+    fn test_bytes_roundtrip() {
+        let config = ExampleConfig {
+            name: "Bytes".to_string(),
+            count: 7,
+        };
+        let bytes: Vec<u8> = store_to_bytes(&config).expect("store_to_bytes failed");
+        let loaded: ExampleConfig = load_from_bytes(&bytes).expect("load_from_bytes failed");
+        assert_eq!(config, loaded);
+
+        #[derive(Serialize)]
+        struct NameOnly {
+            name: String,
+        }
+        let bytes = store_to_bytes(NameOnly {
+            name: "Bytes".to_string(),
+        })
+        .unwrap();
+        match load_from_bytes::<ExampleConfig>(&bytes) {
+            Err(ConfyError::MissingField(field)) => assert_eq!(field, "count"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(load_from_bytes::<ExampleConfig>(&[0xff])
+            .unwrap_err()
+            .is_data());
+    }
+
+    /// [`get_configuration_file_path_with_extension`] only swaps the extension.
+    #[test]
+    fn test_path_with_extension() {
+        let default = get_configuration_file_path("example-app", "example-config")
+            .expect("get_configuration_file_path failed");
+        let custom =
+            get_configuration_file_path_with_extension("example-app", "example-config", "cfg")
+                .expect("get_configuration_file_path_with_extension failed");
+        assert_eq!(custom, default.with_extension("cfg"));
+    }
+
+    /// [`TextNormalization`] transforms compose as documented.
+    #[test]
+    fn test_text_normalization() {
+        let input = "a = 1  \nb = 2\n\n\n";
+        let cases: &[(Option<LineEnding>, bool, bool, &str)] = &[
+            (None, false, false, "a = 1  \nb = 2\n\n\n"),
+            (None, false, true, "a = 1\nb = 2\n\n\n"),
+            (None, true, false, "a = 1  \nb = 2\n"),
+            (None, true, true, "a = 1\nb = 2\n"),
+            (Some(LineEnding::Lf), false, false, "a = 1  \nb = 2\n\n\n"),
+            (
+                Some(LineEnding::CrLf),
+                false,
+                false,
+                "a = 1  \r\nb = 2\r\n\r\n\r\n",
+            ),
+            (
+                Some(LineEnding::CrLf),
+                false,
+                true,
+                "a = 1\r\nb = 2\r\n\r\n\r\n",
+            ),
+            (Some(LineEnding::CrLf), true, false, "a = 1  \r\nb = 2\r\n"),
+            (Some(LineEnding::CrLf), true, true, "a = 1\r\nb = 2\r\n"),
+        ];
+        for (line_ending, trailing_newline, trim_trailing_whitespace, expected) in cases {
+            let normalization = TextNormalization {
+                line_ending: *line_ending,
+                trailing_newline: *trailing_newline,
+                trim_trailing_whitespace: *trim_trailing_whitespace,
+            };
+            assert_eq!(
+                normalization.apply(input.to_string()).as_bytes(),
+                expected.as_bytes(),
+                "{:?}",
+                normalization
+            );
+        }
+
+        let normalization = TextNormalization {
+            trailing_newline: true,
+            ..TextNormalization::default()
+        };
+        assert_eq!(normalization.apply("a = 1".to_string()), "a = 1\n");
+    }
+
+    /// [`store_path_with_options`] applies [`TextNormalization`].
+    #[test]
+    fn test_store_path_with_options_crlf() {
+        with_config_path(|path| {
+            let options = StoreOptions {
+                text: TextNormalization {
+                    line_ending: Some(LineEnding::CrLf),
+                    ..TextNormalization::default()
+                },
+                ..StoreOptions::default()
+            };
+            store_path_with_options(path, ExampleConfig::default(), &options)
+                .expect("store_path_with_options failed");
+            let content = fs::read_to_string(path).expect("reading config failed");
+            assert!(content.contains("\r\n"));
+            assert!(!content.replace("\r\n", "").contains('\n'));
+        })
+    }
+
+    /// A store guard vetoing a store leaves the file unchanged.
+    #[test]
+    fn test_store_guard_veto() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Original".to_string(),
+                count: 1,
+            };
+            store_path(path, &config).expect("store_path failed");
+
+            let guarded = path.to_path_buf();
+            let guard = move |target: &Path, content: &str| {
+                if target == guarded && content.contains("Forbidden") {
+                    return Err("forbidden name".to_string());
+                }
+                Ok(())
+            };
+            let result = with_store_guard(guard, || {
+                store_path(
+                    path,
+                    ExampleConfig {
+                        name: "Forbidden".to_string(),
+                        count: 2,
+                    },
+                )
+            });
+
+            let err = result.expect_err("store_path should be vetoed");
+            assert!(matches!(err, ConfyError::ValidationFailed(msg) if msg == "forbidden name"));
+            let loaded: ExampleConfig = load_path(path).expect("load_path failed");
+            assert_eq!(config, loaded);
+        })
+    }
+
+    /// [`ConfyConfig`] types resolve their path from the associated consts.
+    #[test]
+    fn test_confy_config_path() {
+        struct Named;
+        impl ConfyConfig for Named {
+            const APP_NAME: &'static str = "example-app";
+            const CONFIG_NAME: &'static str = "example-config";
+        }
+
+        struct Unnamed;
+        impl ConfyConfig for Unnamed {
+            const APP_NAME: &'static str = "example-app";
+        }
+
+        assert_eq!(
+            Named::configuration_file_path().unwrap(),
+            get_configuration_file_path("example-app", "example-config").unwrap()
+        );
+        assert_eq!(
+            Unnamed::configuration_file_path().unwrap(),
+            get_configuration_file_path("example-app", None).unwrap()
+        );
+    }
+
+    /// [`load_path_reporting`] only reports creation on the first load.
+    #[test]
+    fn test_load_path_reporting() {
+        with_config_path(|path| {
+            let outcome: LoadOutcome<ExampleConfig> =
+                load_path_reporting(path).expect("load_path_reporting failed");
+            assert!(outcome.created);
+            assert_eq!(outcome.config, ExampleConfig::default());
+            assert!(path.is_file());
+
+            let outcome: LoadOutcome<ExampleConfig> =
+                load_path_reporting(path).expect("load_path_reporting failed");
+            assert!(!outcome.created);
+        })
+    }
+
+    /// [`store_in`] and [`load_in`] use `<base>/<app>/<name>.<ext>`.
+    #[test]
+    fn test_store_load_in() {
+        let base = tempfile::tempdir().expect("creating test fixture failed");
+        let config = ExampleConfig {
+            name: "Based".to_string(),
+            count: 10,
+        };
+        store_in(base.path(), "example-app", None, &config).expect("store_in failed");
+        assert!(base
+            .path()
+            .join("example-app")
+            .join(format!("{}.{}", DEFAULT_CONFIG_NAME, EXTENSION))
+            .is_file());
+        let loaded: ExampleConfig =
+            load_in(base.path(), "example-app", None).expect("load_in failed");
+        assert_eq!(config, loaded);
+    }
+
+    /// [`ConfigScope::Local`] only differs from the roaming path on Windows.
+    #[test]
+    fn test_path_scoped() {
+        let roaming =
+            get_configuration_file_path_scoped("example-app", None, ConfigScope::Roaming).unwrap();
+        let local =
+            get_configuration_file_path_scoped("example-app", None, ConfigScope::Local).unwrap();
+        assert_eq!(
+            roaming,
+            get_configuration_file_path("example-app", None).unwrap()
+        );
+        assert_eq!(roaming == local, !cfg!(windows));
+        assert_eq!(roaming.file_name(), local.file_name());
+    }
+
+    /// [`get_configuration_file_path_with_default`] only applies without a config name.
+    #[test]
+    fn test_path_with_default() {
+        let path = get_configuration_file_path_with_default("example-app", None, "config")
+            .expect("get_configuration_file_path_with_default failed");
+        assert_eq!(path.file_name().unwrap(), &*format!("config.{}", EXTENSION));
+
+        let named = get_configuration_file_path_with_default("example-app", "named", "config")
+            .expect("get_configuration_file_path_with_default failed");
+        assert_eq!(
+            named,
+            get_configuration_file_path("example-app", "named")
+                .expect("get_configuration_file_path failed")
+        );
+    }
+
+    /// [`store_if_changed_path`] only writes when the content differs.
+    #[test]
+    fn test_store_if_changed_path() {
+        with_config_path(|path| {
+            let mut config = ExampleConfig::default();
+            assert!(store_if_changed_path(path, &config).expect("store failed"));
+            assert!(!store_if_changed_path(path, &config).expect("store failed"));
+            config.count = 1;
+            assert!(store_if_changed_path(path, &config).expect("store failed"));
+            let loaded: ExampleConfig = load_path(path).expect("load_path failed");
+            assert_eq!(config, loaded);
+        })
+    }
+
+    /// [`store_path_returning_old`] hands back what was on disk before.
+    #[test]
+    fn test_store_path_returning_old() {
+        with_config_path(|path| {
+            let first = ExampleConfig {
+                name: "First".to_string(),
+                count: 1,
+            };
+            let second = ExampleConfig {
+                name: "Second".to_string(),
+                count: 2,
+            };
+            let old = store_path_returning_old(path, &first).expect("store failed");
+            assert_eq!(old, None);
+            let old = store_path_returning_old(path, &second).expect("store failed");
+            assert_eq!(old, Some(first));
+            let loaded: ExampleConfig = load_path(path).expect("load_path failed");
+            assert_eq!(loaded, second);
+
+            fs::write(path, "not valid").expect("writing config failed");
+            let old = store_path_returning_old(path, &second).expect("store failed");
+            assert_eq!(old, None);
+        })
+    }
+
+    /// Path templates expand into the path the configuration is stored at.
+    #[test]
+    fn test_store_path_template() {
+        with_config_path(|path| {
+            let dir = path.parent().unwrap();
+            let placeholders = template::Placeholders {
+                host: Some("example-host".to_string()),
+                date: "2024-01-31".to_string(),
+                pid: 42,
+            };
+            let template = format!(
+                "{}/config-{{host}}-{{date}}-{{pid}}.{{config_ext}}",
+                dir.display()
+            );
+            let expanded = template::expand(&template, &placeholders).expect("expand failed");
+            assert_eq!(
+                expanded,
+                dir.join(format!("config-example-host-2024-01-31-42.{}", EXTENSION))
+            );
+
+            store_path(&expanded, ExampleConfig::default()).expect("store_path failed");
+            assert!(expanded.is_file());
+
+            // Without `{host}`, the host name is never looked up.
+            let template = format!("{}/config-{{pid}}.{{config_ext}}", dir.display());
+            let stored = store_path_template(&template, ExampleConfig::default())
+                .expect("store_path_template failed");
+            assert_eq!(
+                stored,
+                dir.join(format!("config-{}.{}", std::process::id(), EXTENSION))
+            );
+            assert!(stored.is_file());
+        })
+    }
+
+    /// `{host}` is looked up with `gethostname(2)`, without any environment.
+    #[test]
+    #[cfg(unix)]
+    fn test_path_template_host() {
+        let expanded =
+            template::expand("{host}", &template::Placeholders::current()).expect("expand failed");
+        assert!(!expanded.as_os_str().is_empty());
+    }
+
+    /// [`StoreOptions::pretty`] can be disabled for compact output.
+    #[test]
+    #[cfg(feature = "toml_conf")]
+    fn test_store_path_compact() {
+        with_config_path(|path| {
+            #[derive(Default, Serialize)]
+            struct WithList {
+                list: Vec<usize>,
+            }
+
+            let cfg = WithList {
+                list: vec![1, 2, 3],
+            };
+            let options = StoreOptions {
+                pretty: false,
+                ..StoreOptions::default()
+            };
+            store_path_with_options(path, &cfg, &options).expect("store failed");
+            assert_eq!(fs::read_to_string(path).unwrap(), "list = [1, 2, 3]\n");
+
+            store_path_with_options(path, &cfg, &StoreOptions::default()).expect("store failed");
+            assert_ne!(fs::read_to_string(path).unwrap(), "list = [1, 2, 3]\n");
+        })
+    }
+
+    /// A line based `name=value` format for [`ExampleConfig`].
+    struct KeyValueCodec;
+
+    impl Codec for KeyValueCodec {
+        fn to_string<T: Serialize>(&self, cfg: &T) -> Result<String, ConfyError> {
+            let cfg: ExampleConfig = value::from_value(value::to_value(cfg)?)?;
+            Ok(format!("name={}\ncount={}\n", cfg.name, cfg.count))
+        }
+
+        fn from_str<T: DeserializeOwned>(&self, s: &str) -> Result<T, ConfyError> {
+            let mut fields = s.lines().filter_map(|line| line.split_once('='));
+            let name = fields
+                .next()
+                .map(|(_, v)| v.to_string())
+                .unwrap_or_default();
+            let count = fields
+                .next()
+                .and_then(|(_, v)| v.parse().ok())
+                .unwrap_or_default();
+            value::from_value(value::to_value(&ExampleConfig { name, count })?)
+        }
+    }
+
+    /// [`store_path_with_codec`] and [`load_path_with_codec`] use the given format.
+    #[test]
+    fn test_store_path_with_codec() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_path_with_codec(path, &config, &KeyValueCodec).expect("store failed");
+            let loaded: ExampleConfig =
+                load_path_with_codec(path, &KeyValueCodec).expect("load failed");
+            assert_eq!(loaded, config);
+        })
+    }
+
+    /// [`store_path`] and [`load_path`] preserve all TOML scalar types exactly.
+    #[cfg(feature = "toml_conf")]
+    #[test]
+    fn test_toml_scalars_round_trip() {
+        use toml::value::Datetime;
+
+        #[derive(PartialEq, Debug, Default, Serialize, Deserialize)]
+        struct Scalars {
+            min: i64,
+            max: i64,
+            float: f64,
+            tiny: f64,
+            infinity: f64,
+            flag: bool,
+            text: String,
+            multiline: String,
+            offset_datetime: Option<Datetime>,
+            local_datetime: Option<Datetime>,
+            local_date: Option<Datetime>,
+            local_time: Option<Datetime>,
+            nested: Vec<Vec<i64>>,
+        }
+
+        with_config_path(|path| {
+            let scalars = Scalars {
+                min: i64::MIN,
+                max: i64::MAX,
+                float: -0.1,
+                tiny: 5e-324,
+                infinity: f64::NEG_INFINITY,
+                flag: true,
+                text: "quotes \" and 'apostrophes' \\ and \t tabs".to_string(),
+                multiline: "line one\nline \"two\"\r\n'''three'''\n".to_string(),
+                offset_datetime: "1979-05-27T07:32:00.999999-07:00".parse().ok(),
+                local_datetime: "1979-05-27T00:32:00.999999".parse().ok(),
+                local_date: "1979-05-27".parse().ok(),
+                local_time: "00:32:00.999999".parse().ok(),
+                nested: vec![vec![i64::MIN], vec![], vec![0, i64::MAX]],
+            };
+            assert!(scalars.local_time.is_some());
+            store_path(path, &scalars).expect("store failed");
+            let loaded: Scalars = load_path(path).expect("load failed");
+            assert_eq!(loaded, scalars);
+        })
+    }
+
+    /// TOML serialization errors explain common limitations of the format.
+    #[cfg(feature = "toml_conf")]
+    #[test]
+    fn test_toml_serialize_hints() {
+        let message = |result: Result<String, ConfyError>| result.unwrap_err().to_string();
+        assert!(message(store_preview(vec![1, 2])).contains("top level"));
+
+        let mut map = HashMap::new();
+        map.insert(1, 2);
+        let mut config = HashMap::new();
+        config.insert("map", map);
+        assert!(message(store_preview(&config)).contains("non-string keys"));
+    }
+
+    /// [`get_portable_config_path`] is next to the executable.
+    #[test]
+    fn test_get_portable_config_path() {
+        let exe = std::env::current_exe().unwrap();
+        assert_eq!(
+            get_portable_config_path(None).unwrap(),
+            exe.with_file_name(format!("{}.{}", DEFAULT_CONFIG_NAME, EXTENSION))
+        );
+    }
+
+    /// [`get_config_dir`] is the directory of the configuration files.
+    #[test]
+    fn test_get_config_dir() {
+        let path = get_configuration_file_path("example-app", "example-config").unwrap();
+        assert_eq!(
+            get_config_dir("example-app").unwrap(),
+            path.parent().unwrap()
+        );
+    }
+
+    /// [`config_path_display`] shows the resolved path when there is one.
+    #[test]
+    fn test_config_path_display() {
+        let path = get_configuration_file_path("example-app", "example-config").unwrap();
+        assert_eq!(
+            config_path_display("example-app", "example-config"),
+            path.display().to_string()
+        );
+    }
+
+    /// [`get_configuration_file_path_nested`] joins the segments below the
+    /// configuration directory and rejects segments escaping it.
+    #[test]
+    fn test_get_configuration_file_path_nested() {
+        let path = get_configuration_file_path_nested("example-app", &["profiles", "work"])
+            .expect("nested path failed");
+        let base = get_configuration_file_path("example-app", "x").unwrap();
+        assert_eq!(
+            path,
+            base.with_file_name("profiles")
+                .join(format!("work.{}", EXTENSION))
+        );
+
+        for segments in [&[][..], &[""], &["..", "x"], &["a/../../x"], &["/etc", "x"]] {
+            assert!(matches!(
+                get_configuration_file_path_nested("example-app", segments),
+                Err(ConfyError::BadConfigDirectory(_))
+            ));
+        }
+    }
+
+    /// [`StoreOptions::follow_symlinks`] writes through a symbolic link, or
+    /// replaces it when turned off.
+    #[cfg(unix)]
+    #[test]
+    fn test_store_follow_symlinks() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            let target = path.with_file_name("dotfiles").join("target.toml");
+            fs::create_dir_all(target.parent().unwrap()).unwrap();
+            std::os::unix::fs::symlink(Path::new("dotfiles").join("target.toml"), path).unwrap();
+
+            store_path(path, &config).expect("store failed");
+            assert!(fs::symlink_metadata(path).unwrap().file_type().is_symlink());
+            assert_eq!(load_path::<ExampleConfig>(&target).unwrap(), config);
+
+            let options = StoreOptions {
+                follow_symlinks: false,
+                ..StoreOptions::default()
+            };
+            store_path_with_options(path, ExampleConfig::default(), &options)
+                .expect("store failed");
+            assert!(fs::symlink_metadata(path).unwrap().is_file());
+            assert_eq!(load_path::<ExampleConfig>(&target).unwrap(), config);
+        })
+    }
+
+    /// [`deserialize_config`] reads what [`serialize_config`] writes.
+    #[test]
+    fn test_serialize_deserialize_config() {
+        let config = ExampleConfig {
+            name: "Test".to_string(),
+            count: 42,
+        };
+        let s = serialize_config(&config).expect("serialize_config failed");
+        assert_eq!(s, store_preview(&config).unwrap());
+        let loaded: ExampleConfig = deserialize_config(&s).expect("deserialize_config failed");
+        assert_eq!(loaded, config);
+    }
+
+    /// [`load_from_source`] deserializes the text of a path or closure.
+    #[test]
+    fn test_load_from_source() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_path(path, &config).expect("store failed");
+            let loaded: ExampleConfig = load_from_source(path).expect("load failed");
+            assert_eq!(loaded, config);
+
+            let remote = || store_preview(&config);
+            let loaded: ExampleConfig = load_from_source(&remote).expect("load failed");
+            assert_eq!(loaded, config);
+
+            let failing = || Err(ConfyError::BadConfigDirectory("offline".to_string()));
+            assert!(matches!(
+                load_from_source::<ExampleConfig, _>(&failing),
+                Err(ConfyError::BadConfigDirectory(_))
+            ));
+        })
+    }
+
+    /// [`store_to_writer`] and [`load_from_reader`] round-trip a configuration,
+    /// and an empty standard input gives the default.
+    #[test]
+    fn test_reader_writer() {
+        let config = ExampleConfig {
+            name: "Test".to_string(),
+            count: 42,
+        };
+        let mut buffer = Vec::new();
+        store_to_writer(&mut buffer, &config).expect("store_to_writer failed");
+        let loaded: ExampleConfig = load_from_reader(&buffer[..]).expect("load failed");
+        assert_eq!(loaded, config);
+
+        let loaded: ExampleConfig = load_from_stdin_or_default(&b" \n"[..]).unwrap();
+        assert_eq!(loaded, ExampleConfig::default());
+        let loaded: ExampleConfig = load_from_stdin_or_default(&buffer[..]).unwrap();
+        assert_eq!(loaded, config);
+    }
+
+    /// [`edit_path`] creates the configuration, runs the editor and checks
+    /// the result.
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_path() {
+        with_config_path(|path| {
+            let loaded: ExampleConfig = edit_path_with(path, "true").expect("edit failed");
+            assert_eq!(loaded, ExampleConfig::default());
+            assert!(path.is_file());
+
+            assert!(matches!(
+                edit_path_with::<ExampleConfig>(path, "false"),
+                Err(ConfyError::EditorError(_))
+            ));
+            assert!(matches!(
+                edit_path_with::<ExampleConfig>(path, "confy-no-such-editor"),
+                Err(ConfyError::EditorError(_))
+            ));
+
+            fs::write(path, "count = = 1").unwrap();
+            assert!(edit_path_with::<ExampleConfig>(path, "true")
+                .unwrap_err()
+                .is_data());
+        })
+    }
+
+    /// [`load_path_checked`] detects files not matching the checksum written
+    /// by [`store_path_checked`].
+    #[test]
+    fn test_store_load_path_checked() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_path_checked(path, &config).expect("store failed");
+            let loaded: ExampleConfig = load_path_checked(path).expect("load failed");
+            assert_eq!(loaded, config);
+
+            let mut truncated = fs::read(path).unwrap();
+            truncated.pop();
+            fs::write(path, truncated).unwrap();
+            assert!(matches!(
+                load_path_checked::<ExampleConfig>(path),
+                Err(ConfyError::ChecksumMismatch(_))
+            ));
+
+            fs::remove_file(checksum_path(path)).unwrap();
+            let loaded: ExampleConfig = load_path_checked(path).expect("load failed");
+            assert_eq!(loaded, config);
+
+            // A plain store drops the checksum instead of leaving it stale.
+            store_path_checked(path, &config).expect("store failed");
+            store_path(path, ExampleConfig::default()).expect("store failed");
+            assert!(!checksum_path(path).exists());
+            let loaded: ExampleConfig = load_path_checked(path).expect("load failed");
+            assert_eq!(loaded, ExampleConfig::default());
+        })
+    }
+
+    /// [`store_path_rotating`] shifts numbered versions, skipping gaps and
+    /// dropping the oldest.
+    #[test]
+    fn test_store_path_rotating() {
+        with_config_path(|path| {
+            let version =
+                |n: usize| path.with_file_name(format!("example-config.{}.{}", n, EXTENSION));
+            let count = |path: PathBuf| load_path::<ExampleConfig>(path).unwrap().count;
+            let config = |count| ExampleConfig {
+                name: "Test".to_string(),
+                count,
+            };
+
+            store_path_rotating(path, config(1), 3).expect("store failed");
+            assert!(!version(1).exists());
+            store_path_rotating(path, config(2), 3).expect("store failed");
+            assert_eq!(count(version(1)), 1);
+
+            // The gap at version 1 is skipped, and moves up with the versions.
+            fs::rename(version(1), version(2)).unwrap();
+            store_path_rotating(path, config(3), 3).expect("store failed");
+            assert_eq!(count(path.to_path_buf()), 3);
+            assert_eq!(count(version(1)), 2);
+            assert!(!version(2).exists());
+            assert_eq!(count(version(3)), 1);
+
+            store_path_rotating(path, config(4), 3).expect("store failed");
+            assert_eq!(count(version(1)), 3);
+            assert_eq!(count(version(2)), 2);
+            assert!(!version(3).exists());
+            assert!(!version(4).exists());
+
+            // A vetoed store rotates nothing.
+            let guarded = path.to_path_buf();
+            let guard = move |target: &Path, _: &str| {
+                if target == guarded {
+                    return Err("vetoed".to_string());
+                }
+                Ok(())
+            };
+            let result = with_store_guard(guard, || store_path_rotating(path, config(5), 3));
+            assert!(matches!(result, Err(ConfyError::ValidationFailed(_))));
+            assert_eq!(count(path.to_path_buf()), 4);
+            assert_eq!(count(version(1)), 3);
+            assert_eq!(count(version(2)), 2);
+        })
+    }
+
+    /// [`store_raw_path`] writes valid text verbatim and rejects invalid text.
+    #[test]
+    #[cfg(feature = "toml_conf")]
+    fn test_store_raw_path() {
+        with_config_path(|path| {
+            let contents = "# Kept\ncount = 42 # also kept\nname = \"Test\"\n";
+            store_raw_path(path, contents).expect("store_raw_path failed");
+            assert_eq!(fs::read_to_string(path).unwrap(), contents);
+
+            assert!(store_raw_path(path, "count = = 1").is_err());
+            assert_eq!(fs::read_to_string(path).unwrap(), contents);
+        })
+    }
+
+    /// [`store_path_with_hooks`] runs the hooks before and after writing.
+    #[test]
+    fn test_store_path_with_hooks() {
+        with_config_path(|path| {
+            let mut config = ExampleConfig::default();
+            let mut written = None;
+            store_path_with_hooks(
+                path,
+                &mut config,
+                |cfg| cfg.count += 1,
+                |path| written = Some(load_path::<ExampleConfig>(path).unwrap()),
+            )
+            .expect("store failed");
+            assert_eq!(config.count, 1);
+            assert_eq!(written, Some(config));
+
+            let dir = path.parent().unwrap();
+            let result = store_path_with_hooks(
+                dir,
+                &mut ExampleConfig::default(),
+                |_| {},
+                |_| panic!("post_write called on failure"),
+            );
+            assert!(result.is_err());
+        })
+    }
+
+    /// [`store_path_returning_bytes`] returns the content of the file.
+    #[test]
+    fn test_store_path_returning_bytes() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            let bytes = store_path_returning_bytes(path, &config).expect("store failed");
+            assert_eq!(bytes, fs::read(path).unwrap());
+        })
+    }
+
+    /// [`store_many_path`] writes all configurations, or none if one fails.
+    #[test]
+    fn test_store_many_path() {
+        with_config_path(|path| {
+            let first = ExampleConfig {
+                name: "First".to_string(),
+                count: 1,
+            };
+            let second = ExampleConfig {
+                name: "Second".to_string(),
+                count: 2,
+            };
+            let other = path.with_file_name(format!("other.{}", EXTENSION));
+            store_many_path(&[
+                (path.to_path_buf(), store_preview(&first).unwrap()),
+                (other.clone(), store_preview(&second).unwrap()),
+            ])
+            .expect("store_many_path failed");
+            assert_eq!(load_path::<ExampleConfig>(path).unwrap(), first);
+            assert_eq!(load_path::<ExampleConfig>(&other).unwrap(), second);
+
+            // The checksum of a previous checked store no longer applies.
+            store_path_checked(path, &second).expect("store_path_checked failed");
+            store_many_path(&[(path, store_preview(&first).unwrap())])
+                .expect("store_many_path failed");
+            assert_eq!(load_path_checked::<ExampleConfig>(path).unwrap(), first);
+
+            let dir = path.parent().unwrap();
+            let files = fs::read_dir(dir).unwrap().count();
+            assert!(matches!(
+                store_many_path(&[
+                    (path.to_path_buf(), store_preview(&second).unwrap()),
+                    (dir.to_path_buf(), store_preview(&first).unwrap()),
+                ]),
+                Err(ConfyError::PathIsDirectory(_))
+            ));
+            assert_eq!(load_path::<ExampleConfig>(path).unwrap(), first);
+            assert_eq!(fs::read_dir(dir).unwrap().count(), files);
+        })
+    }
+
+    /// [`wipe`] overwrites the buffer.
+    #[cfg(feature = "secrets")]
+    #[test]
+    fn test_wipe() {
+        let mut bytes = b"api_key = \"secret\"".to_vec();
+        wipe(&mut bytes);
+        assert!(bytes.iter().all(|b| *b == 0));
+    }
+
+    /// Loaders wiping the text they read only do so once it is deserialized.
+    #[cfg(feature = "secrets")]
+    #[test]
+    fn test_wipe_after_load() {
+        let config = ExampleConfig {
+            name: "secret".to_string(),
+            count: 1,
+        };
+        let s = serialize(&config).unwrap();
+        let loaded: ExampleConfig = load_from_stdin_or_default(s.as_bytes()).unwrap();
+        assert_eq!(loaded, config);
+
+        #[cfg(feature = "memory")]
+        {
+            store_mem("secret", &config).unwrap();
+            assert_eq!(load_mem::<ExampleConfig>("secret").unwrap(), config);
+        }
+    }
+
+    /// [`load_path_with_embedded_default`] writes the embedded text when the
+    /// file is missing, and otherwise loads the file.
+    #[test]
+    #[cfg(feature = "toml_conf")]
+    fn test_load_path_with_embedded_default() {
+        with_config_path(|path| {
+            let embedded = "# Bundled\nname = \"bundled\"\ncount = 1\n";
+            assert!(matches!(
+                load_path_with_embedded_default::<ExampleConfig>(path, "count = = 1"),
+                Err(ConfyError::BadTomlData(_))
+            ));
+            assert!(!path.exists());
+
+            let cfg: ExampleConfig =
+                load_path_with_embedded_default(path, embedded).expect("load failed");
+            assert_eq!(cfg.name, "bundled");
+            assert_eq!(fs::read_to_string(path).unwrap(), embedded);
+
+            store_path(path, ExampleConfig::default()).unwrap();
+            let cfg: ExampleConfig =
+                load_path_with_embedded_default(path, embedded).expect("load failed");
+            assert_eq!(cfg, ExampleConfig::default());
+        })
+    }
+
+    /// [`load_strict_path`] reports keys not known to the configuration.
+    #[test]
+    fn test_load_strict_path() {
+        with_config_path(|path| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 1,
+            };
+            fs::write(path, serialize_config(&config).unwrap()).unwrap();
+            let loaded: ExampleConfig = load_strict_path(path).expect("load failed");
+            assert_eq!(loaded.count, 1);
+
+            #[derive(Serialize)]
+            struct Extra {
+                key: usize,
+            }
+            #[derive(Serialize)]
+            struct Misspelled {
+                name: String,
+                count: usize,
+                counts: usize,
+                extra: Extra,
+            }
+            let misspelled = Misspelled {
+                name: "Test".to_string(),
+                count: 1,
+                counts: 2,
+                extra: Extra { key: 3 },
+            };
+            fs::write(path, serialize_config(&misspelled).unwrap()).unwrap();
+            match load_strict_path::<ExampleConfig>(path) {
+                Err(ConfyError::UnknownFields(fields)) => {
+                    assert_eq!(fields, ["counts", "extra.key"])
+                }
+                other => panic!("unexpected result {:?}", other),
+            }
+        })
+    }
+
+    /// [`load_path_with_warnings`] reports created defaults and ignored keys.
+    #[test]
+    fn test_load_path_with_warnings() {
+        with_config_path(|path| {
+            let (loaded, warnings): (ExampleConfig, _) =
+                load_path_with_warnings(path).expect("load failed");
+            assert_eq!(loaded, ExampleConfig::default());
+            assert_eq!(warnings, [Warning::CreatedDefault]);
+
+            #[derive(Serialize)]
+            struct Misspelled {
+                name: String,
+                count: usize,
+                counts: usize,
+            }
+            let misspelled = Misspelled {
+                name: "Test".to_string(),
+                count: 1,
+                counts: 2,
+            };
+            fs::write(path, serialize_config(&misspelled).unwrap()).unwrap();
+            let (loaded, warnings): (ExampleConfig, _) =
+                load_path_with_warnings(path).expect("load failed");
+            assert_eq!(loaded.count, 1);
+            assert_eq!(warnings, [Warning::UnknownField("counts".to_string())]);
+
+            #[derive(Serialize)]
+            struct NameOnly {
+                name: String,
+            }
+            let name_only = NameOnly {
+                name: "Test".to_string(),
+            };
+            fs::write(path, serialize_config(&name_only).unwrap()).unwrap();
+            assert!(load_path_with_warnings::<ExampleConfig>(path).is_err());
+        })
+    }
+
+    /// [`config_metadata_path`] reports the file without creating it.
+    #[test]
+    fn test_config_metadata_path() {
+        with_config_path(|path| {
+            assert_eq!(config_metadata_path(path).expect("metadata failed"), None);
+
+            let s = store_path_returning_bytes(path, ExampleConfig::default()).unwrap();
+            let meta = config_metadata_path(path)
+                .expect("metadata failed")
+                .expect("file missing");
+            assert_eq!(meta.size, s.len() as u64);
+            assert_eq!(
+                meta.modified,
+                fs::metadata(path).unwrap().modified().unwrap()
+            );
+        })
+    }
+
+    /// [`load_with_strategy`] and [`store_with_strategy`] use the directory
+    /// of the strategy.
+    #[test]
+    fn test_path_strategy() {
+        with_config_path(|path| {
+            let dir = path.parent().unwrap().to_path_buf();
+            let strategy = move |app_name: &str| Some(dir.join(app_name));
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_with_strategy(&strategy, "example-app", None, &config).expect("store failed");
+            let loaded: ExampleConfig =
+                load_with_strategy(&strategy, "example-app", None).expect("load failed");
+            assert_eq!(loaded, config);
+            assert_eq!(
+                get_configuration_file_path_with_strategy(&strategy, "example-app", None).unwrap(),
+                path.parent()
+                    .unwrap()
+                    .join("example-app")
+                    .join(format!("{}.{}", DEFAULT_CONFIG_NAME, EXTENSION))
+            );
+
+            let none = |_: &str| None;
+            assert!(matches!(
+                get_configuration_file_path_with_strategy(&none, "example-app", None),
+                Err(ConfyError::BadConfigDirectory(_))
+            ));
+            assert_eq!(
+                ProjectDirsStrategy.config_dir("example-app"),
+                get_config_dir("example-app").ok()
+            );
+        })
+    }
+
+    /// [`try_load_path`] returns `None` for a missing file without creating it.
+    #[test]
+    fn test_try_load_path() {
+        with_config_path(|path| {
+            assert_eq!(
+                try_load_path::<ExampleConfig>(path).expect("load failed"),
+                None
+            );
+            assert!(!path.exists());
+
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_path(path, &config).expect("store failed");
+            assert_eq!(try_load_path(path).expect("load failed"), Some(config));
+
+            fs::write(path, "name = ").unwrap();
+            assert!(try_load_path::<ExampleConfig>(path).is_err());
+        })
+    }
+
+    /// [`load_path_streaming`] loads the same as [`load_path`].
+    #[test]
+    fn test_load_path_streaming() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_path(path, &config).expect("store failed");
+            let loaded: ExampleConfig = load_path_streaming(path).expect("load failed");
+            assert_eq!(loaded, config);
+        })
+    }
+
+    /// [`LoadOptions::create_missing_dirs`] controls whether defaults are
+    /// written to a missing directory.
+    #[test]
+    fn test_load_create_missing_dirs() {
+        with_config_path(|path| {
+            let options = LoadOptions {
+                create_missing_dirs: false,
+                ..LoadOptions::default()
+            };
+            assert!(matches!(
+                load_path_reporting_with_options::<ExampleConfig>(path, &options),
+                Err(ConfyError::BadConfigDirectory(_))
+            ));
+            assert!(!path.parent().unwrap().exists());
+
+            let outcome: LoadOutcome<ExampleConfig> =
+                load_path_reporting_with_options(path, &LoadOptions::default())
+                    .expect("load failed");
+            assert!(outcome.created);
+            fs::remove_file(path).unwrap();
+            let outcome: LoadOutcome<ExampleConfig> =
+                load_path_reporting_with_options(path, &options).expect("load failed");
+            assert!(outcome.created);
+        })
+    }
+
+    /// [`load_or_path`] and [`store_or_path`] use the override path if given.
+    #[test]
+    fn test_load_store_or_path() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_or_path("example-app", None, Some(path), &config).expect("store failed");
+            assert_eq!(load_path::<ExampleConfig>(path).unwrap(), config);
+            let loaded: ExampleConfig =
+                load_or_path("example-app", None, Some(path)).expect("load failed");
+            assert_eq!(loaded, config);
+        })
+    }
+
+    /// Every loader loads an empty file as the default values.
+    #[test]
+    fn test_load_path_empty_file() {
+        with_config_path(|path| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+            let default = ExampleConfig::default();
+            assert_eq!(load_path::<ExampleConfig>(path).unwrap(), default);
+            let loaded: ExampleConfig =
+                load_path_with_options(path, &LoadOptions::default()).expect("load failed");
+            assert_eq!(loaded, default);
+            assert!(try_load_path::<ExampleConfig>(path).unwrap().is_none());
+            let (loaded, warnings) = load_path_with_warnings::<ExampleConfig>(path).unwrap();
+            assert_eq!(loaded, default);
+            assert!(warnings.is_empty());
+            assert_eq!(load_path_checked::<ExampleConfig>(path).unwrap(), default);
+            assert_eq!(load_path_streaming::<ExampleConfig>(path).unwrap(), default);
+            assert_eq!(load_strict_path::<ExampleConfig>(path).unwrap(), default);
+            let outcome = load_path_reporting::<ExampleConfig>(path).unwrap();
+            assert!(!outcome.created);
+            assert_eq!(outcome.config, default);
+            assert_eq!(fs::metadata(path).unwrap().len(), 0);
+
+            let count = crate::with_config_path(path, |cfg: &mut ExampleConfig| {
+                Ok::<_, ConfyError>(cfg.count)
+            })
+            .unwrap();
+            assert_eq!(count, 0);
+        })
+    }
+
+    /// [`migrate_location_path`] moves the file unless the target exists.
+    #[test]
+    fn test_migrate_location_path() {
+        with_config_path(|path| {
+            let new = path.parent().unwrap().join("renamed").join("config.toml");
+            assert!(!migrate_location_path(path, &new).expect("migrate failed"));
+
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            // A checksum left over at the target does not describe the
+            // migrated file.
+            store_path_checked(&new, ExampleConfig::default()).expect("store failed");
+            fs::remove_file(&new).unwrap();
+            store_path(path, &config).expect("store failed");
+            assert!(migrate_location_path(path, &new).expect("migrate failed"));
+            assert!(!path.exists());
+            assert_eq!(load_path_checked::<ExampleConfig>(&new).unwrap(), config);
+
+            store_path(path, ExampleConfig::default()).expect("store failed");
+            assert!(!migrate_location_path(path, &new).expect("migrate failed"));
+            assert!(path.exists());
+            assert_eq!(load_path::<ExampleConfig>(&new).unwrap(), config);
+
+            let vetoed = path.parent().unwrap().join("vetoed.toml");
+            let guarded = vetoed.clone();
+            let guard = move |target: &Path, _: &str| {
+                if target == guarded {
+                    return Err("vetoed".to_string());
+                }
+                Ok(())
+            };
+            let result = with_store_guard(guard, || migrate_location_path(path, &vetoed));
+            assert!(matches!(result, Err(ConfyError::ValidationFailed(_))));
+            assert!(path.exists());
+            assert!(!vetoed.exists());
+        })
+    }
+
+    /// Read-only locations are reported as
+    /// [`ConfyError::ReadOnlyConfigLocation`], files in use as
+    /// [`ConfyError::ConfigFileBusy`].
+    #[test]
+    fn test_write_error() {
+        let path = Path::new("config.toml");
+        for kind in [
+            io::ErrorKind::ReadOnlyFilesystem,
+            io::ErrorKind::PermissionDenied,
+        ] {
+            let err = write_error(kind.into(), path, ConfyError::OpenConfigurationFileError);
+            assert!(matches!(err, ConfyError::ReadOnlyConfigLocation(p) if p == path));
+        }
+        let err = write_error(
+            io::ErrorKind::NotFound.into(),
+            path,
+            ConfyError::OpenConfigurationFileError,
+        );
+        assert!(matches!(err, ConfyError::OpenConfigurationFileError(_)));
+
+        #[cfg(windows)]
+        {
+            // ERROR_SHARING_VIOLATION
+            let err = write_error(
+                io::Error::from_raw_os_error(32),
+                path,
+                ConfyError::OpenConfigurationFileError,
+            );
+            assert!(matches!(err, ConfyError::ConfigFileBusy(p) if p == path));
+        }
+    }
+
+    /// [`StoreOptions::sort_keys`] writes the keys in alphabetical order.
+    #[cfg(feature = "toml_conf")]
+    #[test]
+    fn test_store_sort_keys() {
+        #[derive(Serialize)]
+        struct Unsorted {
+            zebra: u8,
+            apple: u8,
+            inner: Inner,
+        }
+        #[derive(Serialize)]
+        struct Inner {
+            mango: u8,
+            banana: u8,
+        }
+
+        with_config_path(|path| {
+            let cfg = Unsorted {
+                zebra: 1,
+                apple: 2,
+                inner: Inner {
+                    mango: 3,
+                    banana: 4,
+                },
+            };
+            let options = StoreOptions {
+                sort_keys: true,
+                ..StoreOptions::default()
+            };
+            store_path_with_options(path, &cfg, &options).expect("store failed");
+            assert_eq!(
+                fs::read_to_string(path).unwrap(),
+                "apple = 2\nzebra = 1\n\n[inner]\nbanana = 4\nmango = 3\n"
+            );
+        })
+    }
+
+    /// [`load_path_with_dropins`] merges drop-ins in file name order.
+    #[test]
+    fn test_load_path_with_dropins() {
+        with_config_path(|path| {
+            let loaded: ExampleConfig = load_path_with_dropins(path).expect("load failed");
+            assert_eq!(loaded, ExampleConfig::default());
+            assert!(!path.exists());
 
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 1,
+            };
+            store_path(path, &config).expect("store failed");
+            let dir = path.with_extension("d");
+            fs::create_dir_all(&dir).unwrap();
+            let dropin = |name: &str| dir.join(format!("{}.{}", name, EXTENSION));
+            let count = |count: usize| {
+                serialize(&ExampleConfig {
+                    name: "Test".to_string(),
+                    count,
+                })
+                .unwrap()
+            };
+            fs::write(dropin("20-late"), count(3)).unwrap();
+            fs::write(dropin("10-early"), count(2)).unwrap();
+            fs::write(dir.join("99-ignored.txt"), "not a config").unwrap();
+
+            let loaded: ExampleConfig = load_path_with_dropins(path).expect("load failed");
+            assert_eq!(loaded.count, 3);
+        })
+    }
+
+    /// [`is_writable_path`] checks the file and the closest existing directory.
+    #[test]
+    fn test_is_writable_path() {
+        with_config_path(|path| {
+            assert!(is_writable_path(path).expect("check failed"));
+            assert!(!path.parent().unwrap().exists());
+
+            store_path(path, ExampleConfig::default()).expect("store failed");
+            assert!(is_writable_path(path).expect("check failed"));
+
+            // Root may write to read-only files on Unix.
+            let mut perms = fs::metadata(path).unwrap().permissions();
+            perms.set_readonly(true);
+            fs::set_permissions(path, perms).unwrap();
+            assert_eq!(is_writable_path(path).expect("check failed"), is_root());
+        })
+    }
+
+    /// [`is_writable_path`] checks the permissions of the current user, not
+    /// whether anyone may write.
+    #[test]
     #[cfg(unix)]
-    use std::os::unix::fs::PermissionsExt;
+    fn test_is_writable_path_owner() {
+        with_config_path(|path| {
+            let dir = path.parent().unwrap();
+            fs::create_dir_all(dir).expect("creating directory failed");
+            // Writable by everyone but the owner.
+            fs::set_permissions(dir, Permissions::from_mode(0o577))
+                .expect("setting permissions failed");
+            assert_eq!(is_writable_path(path).expect("check failed"), is_root());
+            fs::set_permissions(dir, Permissions::from_mode(0o700))
+                .expect("setting permissions failed");
+        })
+    }
 
-    #[derive(PartialEq, Default, Debug, Serialize, Deserialize)]
-    struct ExampleConfig {
-        name: String,
-        count: usize,
+    /// [`ConfyError::is_io`] and [`ConfyError::is_data`] classify errors.
+    #[test]
+    fn test_error_classification() {
+        let io_error = ConfyError::GeneralLoadError(io::ErrorKind::NotFound.into());
+        assert!(io_error.is_io() && !io_error.is_data());
+        let data_error = deserialize::<ExampleConfig>("name = ").unwrap_err();
+        assert!(data_error.is_data() && !data_error.is_io());
+        let missing = ConfyError::MissingField("count".to_string());
+        assert!(missing.is_data());
+        let template = ConfyError::BadPathTemplate("{".to_string());
+        assert!(!template.is_io() && !template.is_data());
     }
 
-    /// Run a test function with a temporary config path as fixture.
-    fn with_config_path(test_fn: fn(&Path)) {
-        let config_dir = tempfile::tempdir().expect("creating test fixture failed");
-        // config_path should roughly correspond to the result of `get_configuration_file_path("example-app", "example-config")`
-        let config_path = config_dir
-            .path()
-            .join("example-app")
-            .join("example-config")
-            .with_extension(EXTENSION);
-        test_fn(&config_path);
-        config_dir.close().expect("removing test fixture failed");
+    /// Maps work as configurations whose schema is not known in advance.
+    #[test]
+    fn test_load_store_maps() {
+        use std::collections::BTreeMap;
+
+        with_config_path(|path| {
+            let outcome: LoadOutcome<HashMap<String, Value>> =
+                load_path_reporting(path).expect("load failed");
+            assert!(outcome.created && outcome.config.is_empty());
+
+            let mut extras = HashMap::new();
+            extras.insert("name".to_string(), Value::from("Test"));
+            extras.insert("count".to_string(), Value::from(42));
+            store_path(path, &extras).expect("store failed");
+            let loaded: HashMap<String, Value> = load_path(path).expect("load failed");
+            assert_eq!(loaded, extras);
+
+            let loaded: BTreeMap<String, Value> = load_path(path).expect("load failed");
+            assert_eq!(loaded.keys().collect::<Vec<_>>(), ["count", "name"]);
+            store_path(path, &loaded).expect("store failed");
+            assert_eq!(load_path::<ExampleConfig>(path).unwrap().count, 42);
+        })
     }
 
-    /// [`load_path`] loads [`ExampleConfig`].
+    /// [`StoreOptions::create_missing_dirs`] refuses to create the directory.
     #[test]
-    fn load_path_works() {
+    fn test_store_create_missing_dirs() {
         with_config_path(|path| {
-            let config: ExampleConfig = load_path(path).expect("load_path failed");
-            assert_eq!(config, ExampleConfig::default());
+            let options = StoreOptions {
+                create_missing_dirs: false,
+                ..StoreOptions::default()
+            };
+            assert!(matches!(
+                store_path_with_options(path, ExampleConfig::default(), &options),
+                Err(ConfyError::BadConfigDirectory(_))
+            ));
+            assert!(!path.parent().unwrap().exists());
+
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            store_path_with_options(path, ExampleConfig::default(), &options)
+                .expect("store failed");
+            assert!(path.is_file());
         })
     }
 
-    /// [`store_path`] stores [`ExampleConfig`].
+    /// [`resolved_paths`] agrees with the individual path functions.
     #[test]
-    fn test_store_path() {
+    fn test_resolved_paths() {
+        let paths = resolved_paths("example-app", "example-config").expect("resolve failed");
+        assert_eq!(
+            paths.config_file,
+            get_configuration_file_path("example-app", "example-config").unwrap()
+        );
+        assert_eq!(paths.config_file.parent(), Some(paths.config_dir.as_path()));
+        assert_eq!(
+            paths.portable_file,
+            Some(get_portable_config_path("example-config").unwrap())
+        );
+    }
+
+    /// Stored files end with exactly one newline.
+    #[test]
+    fn test_store_trailing_newline() {
+        #[derive(Serialize, Deserialize, Default)]
+        struct Empty {}
+
         with_config_path(|path| {
-            let config: ExampleConfig = ExampleConfig {
+            for pretty in [true, false] {
+                let options = StoreOptions {
+                    pretty,
+                    ..StoreOptions::default()
+                };
+                store_path_with_options(path, ExampleConfig::default(), &options)
+                    .expect("store failed");
+                let content = fs::read_to_string(path).unwrap();
+                assert!(content.ends_with('\n') && !content.ends_with("\n\n"));
+            }
+            assert_eq!(with_trailing_newline("a = 1".to_string()), "a = 1\n");
+            assert!(serialize(&Empty {}).unwrap().ends_with('\n'));
+        })
+    }
+
+    /// [`crate::with_config_path`] only stores when the closure succeeds.
+    #[test]
+    fn test_with_config_path() {
+        with_config_path(|path| {
+            let count = crate::with_config_path(path, |cfg: &mut ExampleConfig| {
+                cfg.count += 1;
+                Ok::<_, ConfyError>(cfg.count)
+            })
+            .expect("with_config_path failed");
+            assert_eq!(count, 1);
+            assert_eq!(load_path::<ExampleConfig>(path).unwrap().count, 1);
+
+            let result = crate::with_config_path(path, |cfg: &mut ExampleConfig| {
+                cfg.count += 1;
+                Err(ConfyError::ValidationFailed("rejected".to_string()))
+            });
+            assert!(matches!(
+                result,
+                Err::<(), _>(ConfyError::ValidationFailed(_))
+            ));
+            assert_eq!(load_path::<ExampleConfig>(path).unwrap().count, 1);
+        })
+    }
+
+    /// [`StoreOptions::write_buffer_size`] writes the same file in chunks.
+    #[test]
+    fn test_store_write_buffer_size() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".repeat(100),
+                count: 42,
+            };
+            for size in [0, 7, 1 << 16] {
+                let options = StoreOptions {
+                    write_buffer_size: Some(size),
+                    ..StoreOptions::default()
+                };
+                store_path_with_options(path, &config, &options).expect("store failed");
+                assert_eq!(
+                    fs::read_to_string(path).unwrap(),
+                    serialize(&config).unwrap()
+                );
+            }
+        })
+    }
+
+    /// [`validate_serializable`] reports what the serializer rejects.
+    #[test]
+    fn test_validate_serializable() {
+        assert!(validate_serializable(&ExampleConfig::default()).is_ok());
+        // A bare array is no valid TOML document, even of tables.
+        #[cfg(feature = "toml_conf")]
+        {
+            assert!(validate_serializable(&vec![1, 2, 3]).is_err());
+            assert!(validate_serializable(&vec![ExampleConfig::default()]).is_err());
+            assert!(validate_serializable(&Some(ExampleConfig::default())).is_ok());
+        }
+    }
+
+    /// [`load_path_any_ext`] finds files with a differently cased extension.
+    #[test]
+    fn test_load_path_any_ext() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
                 name: "Test".to_string(),
                 count: 42,
             };
-            store_path(path, &config).expect("store_path failed");
-            let loaded = load_path(path).expect("load_path failed");
-            assert_eq!(config, loaded);
+            let base = path.with_extension("");
+            let upper = EXTENSION_ALIASES
+                .iter()
+                .find(|extension| **extension == EXTENSION.to_uppercase())
+                .unwrap();
+            store_path(base.with_extension(upper), &config).expect("store failed");
+
+            let loaded: ExampleConfig = load_path_any_ext(&base).expect("load failed");
+            assert_eq!(loaded, config);
+            assert!(matches!(
+                load_path_any_ext::<ExampleConfig>(base.with_file_name("missing")),
+                Err(ConfyError::GeneralLoadError(_))
+            ));
         })
     }
 
-    /// [`store_path_perms`] stores [`ExampleConfig`], with only read permission for owner (UNIX).
+    /// [`load_path_first_of`] loads the first existing file, or creates the
+    /// first one.
+    #[test]
+    fn test_load_path_first_of() {
+        with_config_path(|path| {
+            let old = path.with_file_name(format!("settings.{}", EXTENSION));
+            let paths = [path.to_path_buf(), old.clone()];
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+
+            store_path(&old, &config).unwrap();
+            let loaded: ExampleConfig = load_path_first_of(&paths).expect("load failed");
+            assert_eq!(loaded, config);
+            assert!(!path.exists());
+
+            fs::remove_file(&old).unwrap();
+            let loaded: ExampleConfig = load_path_first_of(&paths).expect("load failed");
+            assert_eq!(loaded, ExampleConfig::default());
+            assert!(path.is_file());
+            assert!(!old.exists());
+
+            assert!(load_path_first_of::<ExampleConfig, PathBuf>(&[]).is_err());
+        })
+    }
+
+    /// [`load_or_else_path`] creates a missing file from the closure, and
+    /// only then calls it.
+    #[test]
+    fn test_load_or_else_path() {
+        #[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+        struct NoDefault {
+            name: String,
+        }
+
+        with_config_path(|path| {
+            let created = load_or_else_path(path, || NoDefault {
+                name: "Test".to_string(),
+            })
+            .expect("load_or_else_path failed");
+            assert_eq!(created.name, "Test");
+
+            let loaded: NoDefault =
+                load_or_else_path(path, || unreachable!()).expect("load_or_else_path failed");
+            assert_eq!(loaded, created);
+        })
+    }
+
+    /// [`StoreOptions::rename_retries`] retries a rename failing because of a
+    /// lock, and only it.
+    #[test]
+    fn test_store_rename_retries() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            let flaky = |failures: u32| {
+                let mut calls = 0;
+                move |from: &Path, to: &Path| {
+                    calls += 1;
+                    if calls <= failures {
+                        Err(io::Error::new(io::ErrorKind::PermissionDenied, "locked"))
+                    } else {
+                        fs::rename(from, to)
+                    }
+                }
+            };
+            let options = StoreOptions {
+                rename_retries: 2,
+                retry_delay: Duration::from_millis(1),
+                ..StoreOptions::default()
+            };
+
+            assert!(matches!(
+                do_store(path, &config, None, &options, flaky(3)),
+                Err(ConfyError::WriteConfigurationFileError(_))
+            ));
+            // The temporary file is removed after failing to move it.
+            assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 0);
+            do_store(path, &config, None, &options, flaky(2)).expect("store failed");
+            let loaded: ExampleConfig = load_path(path).expect("load failed");
+            assert_eq!(loaded, config);
+
+            let mut calls = 0;
+            let missing = |_: &Path, _: &Path| {
+                calls += 1;
+                Err(io::Error::new(io::ErrorKind::NotFound, "gone"))
+            };
+            assert!(matches!(
+                do_store(path, &config, None, &options, missing),
+                Err(ConfyError::WriteConfigurationFileError(_))
+            ));
+            assert_eq!(calls, 1);
+        })
+    }
+
+    /// [`load_value_path`] and [`store_value_path`] round-trip unknown shapes.
+    #[test]
+    fn test_load_store_value_path() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
+            };
+            store_path(path, &config).expect("store failed");
+
+            let mut loaded = load_value_path(path).expect("load_value_path failed");
+            assert_eq!(loaded, value::to_value(&config).unwrap());
+
+            #[derive(Serialize)]
+            struct CountOnly {
+                count: usize,
+            }
+            value::merge(
+                &mut loaded,
+                value::to_value(&CountOnly { count: 7 }).unwrap(),
+            );
+            store_value_path(path, &loaded).expect("store_value_path failed");
+            let config: ExampleConfig = load_path(path).expect("load failed");
+            assert_eq!(config.count, 7);
+            assert!(matches!(
+                load_value_path(path.with_extension("missing")),
+                Err(ConfyError::GeneralLoadError(_))
+            ));
+        })
+    }
+
+    /// [`store_path`] accepts a borrowed configuration, which stays usable.
     #[test]
+    fn test_store_path_borrowed() {
+        with_config_path(|path| {
+            let mut config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 1,
+            };
+            store_path(path, &config).expect("store failed");
+            config.count += 1;
+            store_path(path, &config).expect("store failed");
+            let loaded: ExampleConfig = load_path(path).expect("load failed");
+            assert_eq!(loaded, config);
+        })
+    }
+
+    /// [`StoreOptions::atomic`] turned off writes the file in place.
     #[cfg(unix)]
-    fn test_store_path_perms() {
+    #[test]
+    fn test_store_path_not_atomic() {
+        use std::os::unix::fs::MetadataExt;
+
         with_config_path(|path| {
-            let config: ExampleConfig = ExampleConfig {
-                name: "Secret".to_string(),
-                count: 16549,
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
             };
-            store_path_perms(path, &config, Permissions::from_mode(0o600))
-                .expect("store_path_perms failed");
-            let loaded = load_path(path).expect("load_path failed");
-            assert_eq!(config, loaded);
+            let options = StoreOptions {
+                atomic: false,
+                ..StoreOptions::default()
+            };
+            store_path_with_options(path, ExampleConfig::default(), &options)
+                .expect("store failed");
+            let inode = fs::metadata(path).unwrap().ino();
+            store_path_with_options(path, &config, &options).expect("store failed");
+            assert_eq!(fs::metadata(path).unwrap().ino(), inode);
+            let loaded: ExampleConfig = load_path(path).expect("load failed");
+            assert_eq!(loaded, config);
         })
     }
 
-    /// [`store_path_perms`] stores [`ExampleConfig`], as read-only.
+    /// [`StoreOptions::durable`] stores the configuration like a regular store.
     #[test]
-    fn test_store_path_perms_readonly() {
+    fn test_store_path_durable() {
         with_config_path(|path| {
-            let config: ExampleConfig = ExampleConfig {
-                name: "Soon read-only".to_string(),
-                count: 27115,
+            let config = ExampleConfig {
+                name: "Test".to_string(),
+                count: 42,
             };
-            store_path(path, &config).expect("store_path failed");
+            let options = StoreOptions {
+                durable: true,
+                ..StoreOptions::default()
+            };
+            store_path_with_options(path, &config, &options).expect("store failed");
+            let loaded: ExampleConfig = load_path(path).expect("load failed");
+            assert_eq!(loaded, config);
+        })
+    }
 
-            let metadata = fs::metadata(path).expect("reading metadata failed");
-            let mut permissions = metadata.permissions();
-            permissions.set_readonly(true);
+    /// A missing home directory names the environment variables at fault.
+    #[test]
+    fn test_missing_home_message() {
+        let message = missing_home_message(|_| None);
+        assert!(HOME_VARS.iter().all(|var| message.contains(var)));
+        assert!(message.contains("unset or empty"));
 
-            store_path_perms(path, &config, permissions).expect("store_path_perms failed");
+        let message = missing_home_message(|_| Some("/somewhere".into()));
+        assert!(message.contains("checked"));
+    }
 
-            assert!(fs::metadata(path)
-                .expect("reading metadata failed")
-                .permissions()
-                .readonly());
+    /// [`cleanup_temp_files_path`] only removes stale leftovers of other processes.
+    #[test]
+    fn test_cleanup_temp_files_path() {
+        with_config_path(|path| {
+            store_path(path, ExampleConfig::default()).expect("store_path failed");
+            let dir = path.parent().unwrap();
+            let stale = Duration::from_secs(3600);
+            let touch = |name: &str, age: Duration| {
+                let f = File::create(dir.join(name)).expect("creating file failed");
+                f.set_modified(std::time::SystemTime::now() - age)
+                    .expect("setting mtime failed");
+            };
+            let other_pid = std::process::id().wrapping_add(1);
+            touch(&format!("example-config.{}_a1B2c3.tmp", other_pid), stale);
+            touch(
+                &format!("example-config.{}_ThreadId(2)_123", other_pid),
+                stale,
+            );
+            touch(
+                &format!("example-config.{}_d4E5f6.tmp", other_pid),
+                Duration::ZERO,
+            );
+            touch(
+                &format!("example-config.{}_g7H8i9.tmp", std::process::id()),
+                stale,
+            );
+            touch(&format!("other-config.{}_a1B2c3.tmp", other_pid), stale);
+            touch("example-config.backup", stale);
+
+            assert_eq!(cleanup_temp_files_path(path).expect("cleanup failed"), 2);
+            assert_eq!(fs::read_dir(dir).unwrap().count(), 5);
+            assert!(path.is_file());
         })
     }
 
-    /// [`store_path`] fails when given a root path.
+    /// [`StoreOptions::temp_suffix`] names temporary files, which cleanup finds.
     #[test]
-    fn test_store_path_root_error() {
-        let err = store_path(PathBuf::from("/"), &ExampleConfig::default())
-            .expect_err("store_path should fail");
-        assert_eq!(
-            err.to_string(),
-            r#"Bad configuration directory: "/" is a root or prefix"#,
-        )
+    fn test_store_temp_suffix() {
+        with_config_path(|path| {
+            let options = StoreOptions {
+                temp_suffix: Some(".partial".to_string()),
+                cleanup_temp_files: true,
+                ..StoreOptions::default()
+            };
+            let dir = path.parent().unwrap().to_path_buf();
+            let mut seen = None;
+            do_store(
+                path,
+                ExampleConfig::default(),
+                None,
+                &options,
+                |from, to| {
+                    seen = from.file_name().map(|n| n.to_string_lossy().into_owned());
+                    fs::rename(from, to)
+                },
+            )
+            .expect("store failed");
+            let name = seen.unwrap();
+            assert!(name.ends_with(".partial"), "{}", name);
+
+            let stale = dir.join(format!(
+                "example-config.{}_a1B2c3.partial",
+                std::process::id().wrapping_add(1)
+            ));
+            let f = File::create(&stale).unwrap();
+            f.set_modified(std::time::SystemTime::now() - Duration::from_secs(3600))
+                .unwrap();
+            store_path_with_options(path, ExampleConfig::default(), &options)
+                .expect("store failed");
+            assert!(!stale.exists());
+        })
+    }
+
+    /// [`cleanup_temp_files_path_with_options`] finds temporary files with a
+    /// custom suffix, which [`cleanup_temp_files_path`] leaves alone.
+    #[test]
+    fn test_cleanup_temp_files_path_with_options() {
+        with_config_path(|path| {
+            store_path(path, ExampleConfig::default()).expect("store_path failed");
+            let stale = path.with_file_name(format!(
+                "example-config.{}_a1B2c3.partial",
+                std::process::id().wrapping_add(1)
+            ));
+            let f = File::create(&stale).unwrap();
+            f.set_modified(std::time::SystemTime::now() - Duration::from_secs(3600))
+                .unwrap();
+
+            assert_eq!(cleanup_temp_files_path(path).expect("cleanup failed"), 0);
+            assert!(stale.exists());
+            let options = StoreOptions {
+                temp_suffix: Some(".partial".to_string()),
+                ..StoreOptions::default()
+            };
+            assert_eq!(
+                cleanup_temp_files_path_with_options(path, &options).expect("cleanup failed"),
+                1
+            );
+            assert!(!stale.exists());
+        })
+    }
+
+    /// [`store_path_with_rename`] moves the temporary file with the given closure.
+    #[test]
+    fn test_store_path_with_rename() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Renamed".to_string(),
+                count: 12,
+            };
+            let mut calls = Vec::new();
+            store_path_with_rename(path, &config, |from, to| {
+                calls.push((from.to_path_buf(), to.to_path_buf()));
+                fs::rename(from, to)
+            })
+            .expect("store_path_with_rename failed");
+
+            assert_eq!(calls.len(), 1);
+            let (from, to) = &calls[0];
+            assert_eq!(to, path);
+            assert_ne!(from, path);
+            assert_eq!(from.parent(), path.parent());
+            let loaded: ExampleConfig = load_path(path).expect("load_path failed");
+            assert_eq!(config, loaded);
+        })
+    }
+
+    /// [`store_path_with_comments`] output still loads.
+    #[test]
+    fn test_store_path_with_comments() {
+        with_config_path(|path| {
+            let config = ExampleConfig {
+                name: "Commented".to_string(),
+                count: 4,
+            };
+            store_path_with_comments(path, &config, &[("count", "How many")])
+                .expect("store_path_with_comments failed");
+            let content = fs::read_to_string(path).expect("reading config failed");
+            assert!(content.contains("# How many\ncount"));
+            let loaded: ExampleConfig = load_path(path).expect("load_path failed");
+            assert_eq!(config, loaded);
+        })
     }
 
     struct CannotSerialize;
@@ -483,6 +5956,7 @@ mod tests {
     /// Verify that if you call store_path() with an object that fails to serialize,
     /// the file on disk will not be overwritten or truncated.
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn test_store_path_atomic() -> Result<(), ConfyError> {
         let tmp = tempfile::NamedTempFile::new().expect("Failed to create NamedTempFile");
         let path = tmp.path();
@@ -0,0 +1,80 @@
+//! In-memory configuration storage for tests
+
+use crate::{deserialize, parse_config_string, serialize, ConfyError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    static CONFIGS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+/// Load a configuration from memory
+///
+/// This is an in-memory version of [`load`] for tests: the configuration is
+/// looked up by `key` instead of a path.  Like [`load`], a configuration
+/// with default values is stored and returned if none exists.
+///
+/// Configurations are kept per thread, so tests running in parallel do not
+/// see each other's configurations.
+///
+/// [`load`]: fn.load.html
+pub fn load_mem<T: Serialize + DeserializeOwned + Default>(key: &str) -> Result<T, ConfyError> {
+    match CONFIGS.with(|configs| configs.borrow().get(key).cloned()) {
+        Some(s) => parse_config_string(s, deserialize),
+        None => {
+            let cfg = T::default();
+            store_mem(key, &cfg)?;
+            Ok(cfg)
+        }
+    }
+}
+
+/// Save a configuration to memory
+///
+/// This is an in-memory version of [`store`] for tests, see [`load_mem`].
+/// The configuration is serialized like it would be for a file.
+///
+/// [`store`]: fn.store.html
+/// [`load_mem`]: fn.load_mem.html
+pub fn store_mem<T: Serialize>(key: &str, cfg: T) -> Result<(), ConfyError> {
+    let s = serialize(&cfg)?;
+    CONFIGS.with(|configs| configs.borrow_mut().insert(key.to_string(), s));
+    Ok(())
+}
+
+/// Remove all configurations stored in memory by the current thread.
+pub fn clear_mem() {
+    CONFIGS.with(|configs| configs.borrow_mut().clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(PartialEq, Default, Debug, Serialize, Deserialize)]
+    struct ExampleConfig {
+        name: String,
+        count: usize,
+    }
+
+    #[test]
+    fn round_trip() {
+        let config = ExampleConfig {
+            name: "Test".to_string(),
+            count: 42,
+        };
+        assert_eq!(
+            load_mem::<ExampleConfig>("app").unwrap(),
+            ExampleConfig::default()
+        );
+        store_mem("app", &config).unwrap();
+        assert_eq!(load_mem::<ExampleConfig>("app").unwrap(), config);
+        clear_mem();
+        assert_eq!(
+            load_mem::<ExampleConfig>("app").unwrap(),
+            ExampleConfig::default()
+        );
+    }
+}
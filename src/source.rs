@@ -0,0 +1,34 @@
+//! Configuration sources other than local files
+
+use crate::{read_config_string, ConfyError};
+use std::path::{Path, PathBuf};
+
+/// Where the text of a configuration comes from, see [`load_from_source`]
+///
+/// Implement this to load configurations from e.g. a network service, while
+/// confy takes care of deserializing them.  It is implemented for paths,
+/// and for closures returning the text.
+///
+/// [`load_from_source`]: fn.load_from_source.html
+pub trait Source {
+    /// Read the text of the configuration.
+    fn read(&self) -> Result<String, ConfyError>;
+}
+
+impl Source for Path {
+    fn read(&self) -> Result<String, ConfyError> {
+        read_config_string(self)
+    }
+}
+
+impl Source for PathBuf {
+    fn read(&self) -> Result<String, ConfyError> {
+        read_config_string(self)
+    }
+}
+
+impl<F: Fn() -> Result<String, ConfyError>> Source for F {
+    fn read(&self) -> Result<String, ConfyError> {
+        self()
+    }
+}
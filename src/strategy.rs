@@ -0,0 +1,36 @@
+//! Pluggable policies for the configuration directory
+
+use crate::configuration_directory;
+use std::path::PathBuf;
+
+/// Where an application's configuration directory is, see
+/// [`load_with_strategy`]
+///
+/// Implement this to override the platform conventions followed by default,
+/// e.g. to use `~/.config` on macOS as well.  It is implemented for closures
+/// taking the application name.
+///
+/// [`load_with_strategy`]: fn.load_with_strategy.html
+pub trait PathStrategy {
+    /// The configuration directory of the application `app_name`, or `None`
+    /// if it cannot be determined.
+    fn config_dir(&self, app_name: &str) -> Option<PathBuf>;
+}
+
+/// The platform conventions of the `directories` crate used by [`load`]
+///
+/// [`load`]: fn.load.html
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProjectDirsStrategy;
+
+impl PathStrategy for ProjectDirsStrategy {
+    fn config_dir(&self, app_name: &str) -> Option<PathBuf> {
+        configuration_directory(app_name).ok()
+    }
+}
+
+impl<F: Fn(&str) -> Option<PathBuf>> PathStrategy for F {
+    fn config_dir(&self, app_name: &str) -> Option<PathBuf> {
+        self(app_name)
+    }
+}
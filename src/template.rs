@@ -0,0 +1,145 @@
+//! Expansion of path templates like `config-{host}-{date}.toml`
+
+use crate::{ConfyError, EXTENSION};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Values substituted for the placeholders of a path template.
+///
+/// A `host` of `None` is looked up only if the template contains `{host}`.
+pub struct Placeholders {
+    pub host: Option<String>,
+    pub date: String,
+    pub pid: u32,
+}
+
+impl Placeholders {
+    /// Placeholders describing the current process.
+    pub fn current() -> Self {
+        Placeholders {
+            host: None,
+            date: today(),
+            pid: std::process::id(),
+        }
+    }
+}
+
+/// Expand `{host}`, `{date}`, `{pid}` and `{config_ext}` in `template`.
+pub fn expand(template: &str, placeholders: &Placeholders) -> Result<PathBuf, ConfyError> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            ConfyError::BadPathTemplate(format!("unterminated placeholder in {:?}", template))
+        })?;
+        match &rest[start + 1..start + end] {
+            "host" => match &placeholders.host {
+                Some(host) => out.push_str(host),
+                None => out.push_str(&hostname()?),
+            },
+            "date" => out.push_str(&placeholders.date),
+            "pid" => out.push_str(&placeholders.pid.to_string()),
+            "config_ext" => out.push_str(EXTENSION),
+            name => {
+                return Err(ConfyError::BadPathTemplate(format!(
+                    "unknown placeholder {{{}}} in {:?}",
+                    name, template
+                )))
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(PathBuf::from(out))
+}
+
+/// The host name reported by `gethostname(2)`.
+#[cfg(unix)]
+fn hostname() -> Result<String, ConfyError> {
+    let mut buf = [0u8; 256];
+    // SAFETY: `buf` is valid for writes of `buf.len()` bytes for the duration
+    // of the call.
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return Err(ConfyError::BadPathTemplate(format!(
+            "could not determine host name: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    match String::from_utf8_lossy(&buf[..len]).trim() {
+        "" => Err(ConfyError::BadPathTemplate(
+            "could not determine host name: gethostname returned an empty name".to_string(),
+        )),
+        host => Ok(host.to_string()),
+    }
+}
+
+/// The host name from the `COMPUTERNAME` or `HOSTNAME` environment variable.
+#[cfg(not(unix))]
+fn hostname() -> Result<String, ConfyError> {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|host| host.trim().to_string())
+        .find(|host| !host.is_empty())
+        .ok_or_else(|| {
+            ConfyError::BadPathTemplate(
+                "could not determine host name: neither COMPUTERNAME nor HOSTNAME is set"
+                    .to_string(),
+            )
+        })
+}
+
+/// The current UTC date as `YYYY-MM-DD`.
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Convert days since the UNIX epoch into a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20555), (2026, 4, 12));
+    }
+
+    #[test]
+    fn unknown_placeholder() {
+        let placeholders = Placeholders {
+            host: Some("host".to_string()),
+            date: "date".to_string(),
+            pid: 1,
+        };
+        assert!(matches!(
+            expand("config-{user}.toml", &placeholders),
+            Err(ConfyError::BadPathTemplate(_))
+        ));
+        assert!(matches!(
+            expand("config-{host", &placeholders),
+            Err(ConfyError::BadPathTemplate(_))
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 //! Some storage utilities
 
 use std::fs::File;
-use std::io::{Error as IoError, Read};
+use std::io::{Error as IoError, ErrorKind, Read};
 
 pub trait CheckedStringRead {
     fn get_string(&mut self) -> Result<String, IoError>;
@@ -9,8 +9,104 @@ pub trait CheckedStringRead {
 
 impl CheckedStringRead for File {
     fn get_string(&mut self) -> Result<String, IoError> {
-        let mut s = String::new();
-        self.read_to_string(&mut s)?;
-        Ok(s)
+        // Size the buffer up front, so that growing it does not leave copies
+        // of the content behind in freed memory.
+        let len = self
+            .metadata()
+            .map_or(0, |metadata| metadata.len() as usize);
+        let mut bytes = Vec::with_capacity(len);
+        if let Err(e) = self.read_to_end(&mut bytes) {
+            wipe(&mut bytes);
+            return Err(e);
+        }
+        String::from_utf8(bytes).map_err(|e| {
+            wipe(&mut e.into_bytes());
+            IoError::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+        })
+    }
+}
+
+/// Overwrite `bytes` with zeros, so that e.g. secrets read from a
+/// configuration file do not linger in memory.
+#[cfg(feature = "secrets")]
+pub fn wipe(bytes: &mut [u8]) {
+    bytes.fill(0);
+    // Keep the compiler from optimizing away the writes to a buffer that is
+    // about to be freed.
+    std::hint::black_box(bytes);
+}
+
+/// Without the `secrets` feature, nothing is wiped.
+#[cfg(not(feature = "secrets"))]
+pub fn wipe(_bytes: &mut [u8]) {}
+
+/// Whether any line of `s` is indented with a tab.
+#[cfg(any(feature = "yaml_conf", test))]
+pub fn has_tab_indentation(s: &str) -> bool {
+    s.lines().any(|line| {
+        line.chars()
+            .take_while(|c| c.is_whitespace())
+            .any(|c| c == '\t')
+    })
+}
+
+/// Whether a YAML parser error `message` is caused by a tab, as reported by
+/// the libyaml scanner of `serde_yaml`.
+#[cfg(any(feature = "yaml_conf", test))]
+pub fn is_tab_error(message: &str) -> bool {
+    message.contains("found character that cannot start any token")
+        || message.contains("found a tab character")
+}
+
+/// Expand the tabs in the indentation of every line to tab stops of `width`.
+#[cfg(any(feature = "yaml_conf", test))]
+pub fn expand_leading_tabs(s: &str, width: usize) -> String {
+    let width = width.max(1);
+    let mut out = String::with_capacity(s.len());
+    for line in s.split_inclusive('\n') {
+        let content = line.trim_start_matches([' ', '\t']);
+        let mut column = 0;
+        for c in line[..line.len() - content.len()].chars() {
+            column = if c == '\t' {
+                (column / width + 1) * width
+            } else {
+                column + 1
+            };
+        }
+        out.push_str(&" ".repeat(column));
+        out.push_str(content);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_leading_tabs() {
+        let input = "a:\n\tb: 1\n  \tc: \"\t\"\n";
+        assert!(has_tab_indentation(input));
+        assert_eq!(expand_leading_tabs(input, 2), "a:\n  b: 1\n    c: \"\t\"\n");
+        assert_eq!(
+            expand_leading_tabs(input, 4),
+            "a:\n    b: 1\n    c: \"\t\"\n"
+        );
+        assert!(!has_tab_indentation("a: \"\t\"\n"));
+    }
+
+    #[test]
+    fn detects_tab_errors() {
+        assert!(is_tab_error(
+            "found character that cannot start any token at line 2 column 1, \
+             while scanning for the next token"
+        ));
+        assert!(is_tab_error(
+            "found a tab character that violates indentation at line 3 column 1"
+        ));
+        assert!(!is_tab_error("missing field `name` at line 1 column 1"));
+        assert!(!is_tab_error(
+            "mapping values are not allowed in this context at line 1 column 6"
+        ));
     }
 }
@@ -0,0 +1,121 @@
+//! Dynamic value trees of the enabled config language
+
+use crate::ConfyError;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A dynamic configuration document: `toml::Value` or `serde_yaml::Value`,
+/// depending on the enabled config language.
+#[cfg(feature = "toml_conf")]
+pub type Value = toml::Value;
+
+/// A dynamic configuration document: `toml::Value` or `serde_yaml::Value`,
+/// depending on the enabled config language.
+#[cfg(feature = "yaml_conf")]
+pub type Value = serde_yaml::Value;
+
+/// An empty table, the root of an empty configuration.
+pub fn empty() -> Value {
+    #[cfg(feature = "toml_conf")]
+    {
+        Value::Table(Default::default())
+    }
+    #[cfg(feature = "yaml_conf")]
+    {
+        Value::Mapping(Default::default())
+    }
+}
+
+/// Convert a configuration object into a value tree.
+pub fn to_value<T: Serialize>(cfg: &T) -> Result<Value, ConfyError> {
+    #[cfg(feature = "toml_conf")]
+    {
+        Value::try_from(cfg).map_err(ConfyError::SerializeTomlError)
+    }
+    #[cfg(feature = "yaml_conf")]
+    {
+        serde_yaml::to_value(cfg).map_err(ConfyError::SerializeYamlError)
+    }
+}
+
+/// Convert a value tree into a configuration object.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, ConfyError> {
+    #[cfg(feature = "toml_conf")]
+    {
+        value.try_into().map_err(ConfyError::BadTomlData)
+    }
+    #[cfg(feature = "yaml_conf")]
+    {
+        serde_yaml::from_value(value).map_err(ConfyError::BadYamlData)
+    }
+}
+
+/// Deep-merge `overlay` into `base`.
+///
+/// Tables are merged key by key, any other value in `overlay` replaces the
+/// one in `base`.
+pub fn merge(base: &mut Value, overlay: Value) {
+    #[cfg(feature = "toml_conf")]
+    match (base, overlay) {
+        (Value::Table(base), Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+    #[cfg(feature = "yaml_conf")]
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Collect the dotted paths of all non-table values in `value`.
+pub fn leaves(value: &Value, prefix: &str, out: &mut Vec<String>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+
+    #[cfg(feature = "toml_conf")]
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                leaves(value, &join(key), out);
+            }
+        }
+        _ => out.push(prefix.to_string()),
+    }
+    #[cfg(feature = "yaml_conf")]
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let key = match key {
+                    Value::String(key) => key.clone(),
+                    key => serde_yaml::to_string(key)
+                        .map(|k| k.trim_end().to_string())
+                        .unwrap_or_default(),
+                };
+                leaves(value, &join(&key), out);
+            }
+        }
+        _ => out.push(prefix.to_string()),
+    }
+}
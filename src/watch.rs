@@ -0,0 +1,150 @@
+//! Watching configuration files for external changes
+
+use crate::{get_configuration_file_path, load_path, ConfyError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Handle to a running configuration watch, see [`watch`].
+///
+/// Watching stops when the handle is dropped.
+///
+/// [`watch`]: fn.watch.html
+#[derive(Debug)]
+pub struct Watcher {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        // Disconnecting the channel wakes up and ends the watch thread.
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Watch an application configuration for external changes
+///
+/// `callback` is called from a background thread with the reloaded
+/// configuration whenever the configuration file is changed, e.g. by the
+/// user editing it.  Errors, like a configuration that does not parse, are
+/// handed to `callback` as well, so that the application can keep using the
+/// last good configuration.
+///
+/// The file is polled for changes of its modification time and size.  Bursts
+/// of changes are debounced: the configuration is only reloaded once the file
+/// has not changed for one polling interval.
+///
+/// Watching stops when the returned [`Watcher`] is dropped.
+///
+/// ```rust,no_run
+/// # use confy::ConfyError;
+/// # use serde_derive::{Serialize, Deserialize};
+/// # fn main() -> Result<(), ConfyError> {
+/// #[derive(Debug, Default, Serialize, Deserialize)]
+/// struct MyConfig {}
+///
+/// let _watcher = confy::watch("my-app-name", None, |cfg: Result<MyConfig, _>| match cfg {
+///     Ok(cfg) => println!("reloaded {:?}", cfg),
+///     Err(e) => eprintln!("keeping the last good configuration: {}", e),
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Watcher`]: struct.Watcher.html
+pub fn watch<'a, T, F>(
+    app_name: &str,
+    config_name: impl Into<Option<&'a str>>,
+    callback: F,
+) -> Result<Watcher, ConfyError>
+where
+    T: Serialize + DeserializeOwned + Default,
+    F: Fn(Result<T, ConfyError>) + Send + 'static,
+{
+    let path = get_configuration_file_path(app_name, config_name)?;
+    Ok(watch_path(path, callback))
+}
+
+/// Watch a configuration at a specified path for external changes
+///
+/// This is an alternate version of [`watch`] that allows the specification of
+/// an arbitrary path instead of a system one.
+///
+/// [`watch`]: fn.watch.html
+pub fn watch_path<T, F>(path: impl AsRef<Path>, callback: F) -> Watcher
+where
+    T: Serialize + DeserializeOwned + Default,
+    F: Fn(Result<T, ConfyError>) + Send + 'static,
+{
+    let path = path.as_ref().to_path_buf();
+    let (stop, stopped) = mpsc::channel::<()>();
+    let mut last = stamp(&path);
+
+    let thread = thread::spawn(move || {
+        let mut pending = false;
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(POLL_INTERVAL) {
+            let current = stamp(&path);
+            if current != last {
+                last = current;
+                pending = true;
+            } else if pending {
+                pending = false;
+                callback(load_path(&path));
+            }
+        }
+    });
+
+    Watcher {
+        stop: Some(stop),
+        thread: Some(thread),
+    }
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store_path;
+    use serde_derive::{Deserialize, Serialize};
+    use std::sync::mpsc::channel;
+
+    #[derive(PartialEq, Default, Debug, Serialize, Deserialize)]
+    struct ExampleConfig {
+        count: usize,
+    }
+
+    #[test]
+    fn watch_path_reports_changes_and_errors() {
+        let dir = tempfile::tempdir().expect("creating test fixture failed");
+        let path = dir.path().join("example-config");
+        store_path(&path, ExampleConfig { count: 1 }).expect("store_path failed");
+
+        let (tx, rx) = channel();
+        let watcher = watch_path(&path, move |cfg: Result<ExampleConfig, _>| {
+            tx.send(cfg.map_err(|e| e.to_string())).unwrap();
+        });
+
+        store_path(&path, ExampleConfig { count: 22 }).expect("store_path failed");
+        let cfg = rx.recv_timeout(Duration::from_secs(5)).expect("no reload");
+        assert_eq!(cfg, Ok(ExampleConfig { count: 22 }));
+
+        fs::write(&path, "count = [").expect("writing config failed");
+        let cfg = rx.recv_timeout(Duration::from_secs(5)).expect("no reload");
+        assert!(cfg.is_err());
+
+        drop(watcher);
+    }
+}